@@ -1,4 +1,5 @@
-use critfail::{Roll, RollExpression};
+use critfail::{Roll, RollExpression, Score};
+use std::collections::HashMap;
 use std::error::Error;
 
 pub fn run_args(args: Vec<String>) -> Result<(), Box<dyn Error>> {
@@ -8,30 +9,48 @@ pub fn run_args(args: Vec<String>) -> Result<(), Box<dyn Error>> {
     } else if args.iter().any(|s| s == "-V" || s == "--version") {
         print_version();
     } else {
-        make_roll(&args[1])?
+        make_roll(&args[1], &args[2..])?
     }
 
     Ok(())
 }
 
-fn make_roll(roll: &str) -> Result<(), Box<dyn Error>> {
-    let result = Roll::new(roll)?.roll();
+fn make_roll(roll: &str, variables: &[String]) -> Result<(), Box<dyn Error>> {
+    let context = parse_variables(variables)?;
+    // Point out where the problem is in the expression
+    let roll = Roll::new(roll).map_err(|err| format!("{}\n{}\n{}", roll, err.underline(), err))?;
+    let result = roll.roll_with_context(&context)?;
     println!("{:?}\n{}", result, result);
 
     Ok(())
 }
 
+/// Parse the values of variables given as `name=value` arguments.
+fn parse_variables(args: &[String]) -> Result<HashMap<String, Score>, Box<dyn Error>> {
+    let mut context = HashMap::new();
+    for arg in args {
+        let (name, value) = arg
+            .split_once('=')
+            .ok_or_else(|| format!("expected a variable like `str=3`, found `{}`", arg))?;
+        let name = name.trim_start_matches('$');
+        context.insert(name.to_string(), value.parse()?);
+    }
+    Ok(context)
+}
+
 fn print_usage() {
     println!(
         "Usage:
-    critfail [roll expression]
-        roll the expression and show the result
+    critfail [roll expression] [name=value]...
+        roll the expression and show the result, using the given values
+        for any variables in it
 Options:
     -v, --version  Show version info
     -h, --help     Show this help message
 
 Roll Expressions:
-    There are 3 kinds of roll expressions: Checks, damage, and attacks.
+    There are 4 kinds of roll expressions: Checks, damage, attacks and
+    saves. Any of them can be repeated several times.
 
     r+6 : A check. Roll a d20 and add 6.
     2d6+4 : Damage roll. Rolls 2d8 and adds 4.
@@ -49,10 +68,42 @@ Roll Expressions:
     `d+4` : roll a d20 with disadvantage then add 4.
     `d+4+1d4` : roll a d20 with disadvantage, then add 4.+1d4
 
+    The d20 can be given a minimum value, separated by a space:
+    `r+7 min10` : roll a d20, treating anything below 10 as a 10, then add 7.
+
+    Checks can crit on a wider range than just a 20:
+    `r+5c19` : roll a d20 and add 5, scoring a critical on a 19 or 20.
+
+    Checks can be rolled against a DC to see if they succeed:
+    `r+5 vs 15` : roll a d20 and add 5, succeeding on 15 or higher.
+
     Damage:
     Roll multiple dice to determine damage.
     `2d8+5`
     `2d8-1d4+7-2`
+    `d8 + (1d6+2)` : a single die can leave out the count, and parts can be
+                     grouped with parentheses.
+    `(2d6+3)*2` : roll 2d6, add 3 and double the total.
+    `8d6/2` : roll 8d6 and halve the total, rounding down.
+
+    Dice can keep or drop some of the rolled dice:
+    `4d6kh3` : roll 4d6 and keep the highest 3.
+    `2d20kl1` : roll 2d20 and keep the lowest one.
+    `4d6dl1` : roll 4d6 and drop the lowest one. (`dh` drops the highest)
+
+    Dice can explode, rolling again and adding when they roll high:
+    `1d6!` : roll 1d6, and roll again each time it rolls a 6.
+    `2d10!>9` : roll 2d10, and roll again each time one rolls 9 or higher.
+
+    Dice can be rerolled when they roll low:
+    `2d6r<2` : roll 2d6, rerolling each die once if it rolls 2 or lower.
+    `1d8rr<1` : roll 1d8, rerolling it until it rolls higher than 1.
+
+    Dice can have a minimum value:
+    `8d6min2` : roll 8d6, treating any 1s as 2s.
+
+    Damage can have types, which apply to the parts before them:
+    `1d8+3 slashing + 2d6 fire`
 
     Attacks:
     An attack consts of both a check and a damage roll, separated by a `?`.
@@ -61,7 +112,38 @@ Roll Expressions:
 
     If the check part of an attack rolls a 20, all of the positive dice in
     the damage part of the roll will be rolled twice. (Modifiers will only
-    be counted once)."
+    be counted once). A crit range on the check also applies to attacks:
+    `r+5c19?1d8+3`
+
+    The damage can use a different rule for critical hits, separated by
+    a space:
+    `r+5?2d6+3 crit max` : count the dice as their max, then roll them.
+    `r+5?2d6+3 crit total` : roll the damage once and double the total.
+    `r+5?1d8+3+2d6 crit 1` : only roll the second set of dice twice.
+
+    Attacks can be rolled against an AC to see if they hit. A miss deals
+    no damage.
+    `r+7?1d8+4 vs AC 16`
+
+    Saves:
+    `save DC[dc] [ability] [+bonus] : [damage] [half|negates]`
+    A save-based effect rolls the target's saving throw against a DC, and
+    rolls damage once. The target takes half damage if it saves, or none
+    if the effect `negates` the damage. The ability and bonus can be left
+    out, and the damage is halved if neither `half` nor `negates` is given.
+    `save DC15 dex +3 : 8d6 half`
+    `save DC12 con -1 : 1d12 poison negates`
+
+    Repeated rolls:
+    Any roll can be rolled several times by starting it with a count.
+    Attacks show the total damage from the attacks that hit.
+    `3x r+5?1d8+3` : make three attacks.
+    `2x r+2 vs 12` : roll two checks against DC 12.
+
+    Variables:
+    Numbers can be replaced with variables, which are given values after
+    the expression.
+    `r+$str+$prof?1d8+$str str=3 prof=2`"
     )
 }
 
@@ -10,10 +10,12 @@ mod style;
 mod examples;
 mod expression_box;
 mod result_box;
+mod variables_box;
 
 use examples::*;
 use expression_box::*;
 use result_box::*;
+use variables_box::*;
 
 pub fn run() {
     Window::run(Settings::default());
@@ -24,6 +26,8 @@ struct Window {
 
     /// Entries for roll expressions
     expressions: Vec<ExpressionBox>,
+    /// Values for the variables used in the expressions
+    variables: VariablesBox,
     expressions_scroll: scrollable::State,
     help_scroll: scrollable::State,
     /// Button to add an expression box
@@ -43,6 +47,7 @@ struct Window {
 #[derive(Debug, Clone)]
 enum Message {
     ExpressionMsg(usize, ExpressionMsg),
+    VariableMsg(VariableMsg),
     AddPressed,
     ToggleView,
     ExampleRolled(SectionId, usize, Option<AdvState>),
@@ -82,16 +87,26 @@ impl Sandbox for Window {
                 .push(Example::new(
                     "A more complicated damage roll",
                     "3d12-1d4+6-2",
-                )),
+                ))
+                .push(Example::new("Roll 4d6 and keep the highest 3", "4d6kh3")),
             examples_attack: ExampleGroup::new(SectionId::Attack)
                 .push(Example::new("+3 to hit, 1d8 of damage", "+3?1d8"))
                 .push(Example::new(
                     "+5 to hit, 1d4+4+5d6 of damage",
                     "+5?1d4+4+5d6",
+                ))
+                .push(Example::new(
+                    "Two attacks at +5 to hit against AC 15",
+                    "2x r+5?1d8+3 vs AC 15",
+                ))
+                .push(Example::new(
+                    "A Fireball against a target with +3 to Dex saves",
+                    "save DC15 dex +3 : 8d6 half",
                 )),
 
             view: Default::default(),
             expressions: Default::default(),
+            variables: Default::default(),
             expressions_scroll: Default::default(),
             help_scroll: Default::default(),
             add_button: Default::default(),
@@ -108,7 +123,7 @@ impl Sandbox for Window {
         match message {
             Message::ExpressionMsg(i, ExpressionMsg::RollPressed(adv)) => {
                 let expression = &self.expressions[i];
-                let result = expression.roll(adv);
+                let result = expression.roll(adv, &self.variables.context());
 
                 self.result_box
                     .update(ResultMessage::from_roll(expression, result))
@@ -117,6 +132,7 @@ impl Sandbox for Window {
                 self.expressions.remove(i);
             }
             Message::ExpressionMsg(i, msg) => self.expressions[i].update(msg),
+            Message::VariableMsg(msg) => self.variables.update(msg),
             Message::AddPressed => self.expressions.push(ExpressionBox::new()),
             Message::ToggleView => {
                 self.view = match self.view {
@@ -135,7 +151,7 @@ impl Sandbox for Window {
         }
     }
 
-    fn view(&mut self) -> Element<Message> {
+    fn view(&mut self) -> Element<'_, Message> {
         let title_bar = Row::new()
             .push(
                 Container::new(
@@ -177,6 +193,14 @@ impl Sandbox for Window {
                     self.expressions.push(ExpressionBox::new())
                 }
 
+                // Every variable used by any of the expressions
+                let mut names: Vec<String> = vec![];
+                for name in self.expressions.iter().flat_map(|exp| exp.variables()) {
+                    if !names.contains(&name) {
+                        names.push(name);
+                    }
+                }
+
                 let expressions = self.expressions.iter_mut().enumerate().fold(
                     Column::new().spacing(20),
                     |col, (i, exp)| {
@@ -197,11 +221,19 @@ impl Sandbox for Window {
                 .on_press(Message::AddPressed)
                 .style(style::Button::Secondary);
 
-                Column::new()
+                let mut main = Column::new()
                     .spacing(20)
                     .align_items(Align::Center)
-                    .push(self.result_box.view())
-                    .push(
+                    .push(self.result_box.view());
+                if !names.is_empty() {
+                    main = main.push(
+                        self.variables
+                            .view(&names)
+                            .map(Message::VariableMsg),
+                    );
+                }
+
+                main.push(
                         Scrollable::new(&mut self.expressions_scroll)
                             .spacing(20)
                             .push(expressions)
@@ -232,6 +264,9 @@ impl Sandbox for Window {
                 .push(style::text::paragraph("An attack consts of both a check and a damage roll, separated by a '?'. If the check part of an attack rolls a 20, all of the positive dice in the damage part of the roll will be rolled twice. (Modifiers will only be counted once)."))
                 .push(self.examples_attack.view())
 
+                .push(style::text::header("Variables"))
+                .push(style::text::paragraph("Expressions can use variables like `$str` or `$prof` instead of numbers, eg. `r+$str+$prof?1d8+$str`. A box to enter the value of each variable appears above the expressions that use it. Every expression uses the same values, so they can all be updated at once, eg. when your character levels up."))
+
                 .push(style::text::header("About"))
                 .push(Text::new(format!("Critfail v{}-{}", VERSION, GIT_VERSION)))
                 .push(Button::new(&mut self.shameless_plug_button, Text::new("View on GitHub")).on_press(Message::OpenGitHub).style(style::Button::Primary))
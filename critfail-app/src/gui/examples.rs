@@ -50,7 +50,7 @@ impl ExampleGroup {
         self
     }
 
-    pub fn view(&mut self) -> Element<Message> {
+    pub fn view(&mut self) -> Element<'_, Message> {
         let section = self.section;
 
         self.examples
@@ -86,7 +86,7 @@ impl Example {
         }
     }
 
-    pub fn view(&mut self) -> Element<ExampleMessage> {
+    pub fn view(&mut self) -> Element<'_, ExampleMessage> {
         let mut buttons = Row::new()
             .spacing(10)
             .push(Text::new(self.expression).width(Length::Units(300)))
@@ -96,22 +96,19 @@ impl Example {
                     .on_press(ExampleMessage::Roll(None)),
             );
 
-        match self.roll {
-            Roll::Check(_) | Roll::Attack(_) => {
-                buttons = buttons
-                    .push(
-                        Button::new(&mut self.adv_button, Text::new("Adv."))
-                            .style(style::Button::Primary)
-                            .on_press(ExampleMessage::Roll(Some(AdvState::Advantage))),
-                    )
-                    .push(
-                        Button::new(&mut self.dis_button, Text::new("Dis."))
-                            .style(style::Button::Primary)
-                            .on_press(ExampleMessage::Roll(Some(AdvState::Disadvantage))),
-                    );
-            }
-            _ => (),
-        };
+        if self.roll.can_have_advantage() {
+            buttons = buttons
+                .push(
+                    Button::new(&mut self.adv_button, Text::new("Adv."))
+                        .style(style::Button::Primary)
+                        .on_press(ExampleMessage::Roll(Some(AdvState::Advantage))),
+                )
+                .push(
+                    Button::new(&mut self.dis_button, Text::new("Dis."))
+                        .style(style::Button::Primary)
+                        .on_press(ExampleMessage::Roll(Some(AdvState::Disadvantage))),
+                );
+        }
 
         Column::new()
             .spacing(10)
@@ -122,14 +119,10 @@ impl Example {
 
     pub fn roll(&self, adv: Option<AdvState>) -> ResultMessage {
         let outcome = match adv {
-            Some(adv) => match &self.roll {
-                Roll::Check(check) => check.roll_with_advantage(adv).into(),
-                Roll::Attack(attack) => attack.roll_with_advantage(adv).into(),
-                Roll::Damage(damage) => {
-                    debug_assert!(false, "The roll with advantage/disadvantage buttons shouldn't ever be visible for a damage roll.");
-                    damage.roll().into()
-                }
-            },
+            Some(adv) => {
+                debug_assert!(self.roll.can_have_advantage(), "The roll with advantage/disadvantage buttons shouldn't ever be visible for a damage roll.");
+                self.roll.roll_with_advantage(adv)
+            }
             None => self.roll.roll(),
         };
 
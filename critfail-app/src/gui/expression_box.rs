@@ -1,10 +1,11 @@
 //! This module defines the element that is used for entering attacks.
 use crate::gui::style;
-use critfail::{AdvState, ParseError, Roll, RollExpression, RollOutcome};
+use critfail::{AdvState, ParseError, Roll, RollExpression, RollOutcome, Score};
 use iced::{
-    button, text_input, Align, Button, Column, Element, HorizontalAlignment, Length, Row, Text,
-    TextInput, VerticalAlignment,
+    button, text_input, Align, Button, Color, Column, Element, HorizontalAlignment, Length, Row,
+    Text, TextInput, VerticalAlignment,
 };
+use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
 /// Messages that can be sent by an expression box.
@@ -60,6 +61,7 @@ const TEXTENTRY_SIZE: u16 = 30;
 #[cfg(not(target_arch = "wasm32"))]
 const TEXTENTRY_SIZE: u16 = 40;
 const ADV_TEXT_SIZE: u16 = TEXTENTRY_SIZE / 2;
+const ERROR_TEXT_SIZE: u16 = TEXTENTRY_SIZE / 2;
 
 impl ExpressionBox {
     pub(super) fn new() -> Self {
@@ -76,14 +78,14 @@ impl ExpressionBox {
                 self.has_adv = self
                     .roll
                     .as_ref()
-                    .map(|r| r.is_check() || r.is_attack())
+                    .map(|r| r.can_have_advantage())
                     .unwrap_or(self.has_adv);
                 self.expression = expression;
             }
         }
     }
 
-    pub(super) fn view(&mut self) -> Element<ExpressionMsg> {
+    pub(super) fn view(&mut self) -> Element<'_, ExpressionMsg> {
         let roll_box = TextInput::new(
             &mut self.roll_box,
             "Dice",
@@ -159,16 +161,19 @@ impl ExpressionBox {
         .style(style::Button::Secondary)
         .on_press(ExpressionMsg::DeletePressed);
 
+        let mut entries = Column::new()
+            .width(Length::Fill)
+            .push(name_box)
+            .push(roll_box);
+        if let (Err(err), false) = (&self.roll, self.expression.is_empty()) {
+            entries = entries.push(error_line(&self.expression, err));
+        }
+
         Row::new()
             .spacing(20)
             .align_items(Align::Start)
             .push(delete_button)
-            .push(
-                Column::new()
-                    .width(Length::Fill)
-                    .push(name_box)
-                    .push(roll_box),
-            )
+            .push(entries)
             .push(roll_button)
             .height(Length::Shrink)
             .into()
@@ -182,20 +187,55 @@ impl ExpressionBox {
         &self.expression
     }
 
-    pub(super) fn roll(&self, adv: Option<AdvState>) -> Result<RollOutcome, String> {
+    /// Get the names of the variables in the expression, if it is
+    /// valid.
+    pub(super) fn variables(&self) -> Vec<String> {
+        match &self.roll {
+            Ok(roll) => roll.variables().into_iter().map(String::from).collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    pub(super) fn roll(
+        &self,
+        adv: Option<AdvState>,
+        context: &HashMap<String, Score>,
+    ) -> Result<RollOutcome, String> {
         let roll = Roll::new(&self.expression).map_err(|err| format!("{}", err))?;
+        let roll = roll.bind(context).map_err(|err| format!("{}", err))?;
 
         let outcome = match adv {
-            Some(adv) => match roll {
-                Roll::Check(check) => check.roll_with_advantage(adv).into(),
-                Roll::Attack(attack) => attack.roll_with_advantage(adv).into(),
-                Roll::Damage(damage) => {
-                    debug_assert!(false, "The roll with advantage/disadvantage buttons shouldn't ever be visible for a damage roll.");
-                    damage.roll().into()
-                }
-            },
+            Some(adv) => {
+                debug_assert!(roll.can_have_advantage(), "The roll with advantage/disadvantage buttons shouldn't ever be visible for a damage roll.");
+                roll.roll_with_advantage(adv)
+            }
             None => roll.roll(),
         };
         Ok(outcome)
     }
 }
+
+/// Show the expression with the part that couldn't be parsed
+/// highlighted in red, followed by what went wrong.
+fn error_line<'a>(expression: &str, err: &ParseError) -> Element<'a, ExpressionMsg> {
+    let red = Color::from_rgb(0.8, 0.1, 0.1);
+    let span = err.span();
+
+    let before = expression.get(..span.start).unwrap_or_default();
+    let after = expression.get(span.end..).unwrap_or_default();
+    // Mark the spot if something is missing rather than wrong
+    let bad = match expression.get(span) {
+        Some(bad) if !bad.is_empty() => bad,
+        _ => "_",
+    };
+
+    Column::new()
+        .push(
+            Row::new()
+                .push(Text::new(before).size(ERROR_TEXT_SIZE))
+                .push(Text::new(bad).size(ERROR_TEXT_SIZE).color(red))
+                .push(Text::new(after).size(ERROR_TEXT_SIZE)),
+        )
+        .push(Text::new(err.to_string()).size(ERROR_TEXT_SIZE).color(red))
+        .into()
+}
@@ -57,7 +57,7 @@ impl ResultBox {
         }
     }
 
-    pub(super) fn view(&mut self) -> Element<super::Message> {
+    pub(super) fn view(&mut self) -> Element<'_, super::Message> {
         let name_text = Text::new(&self.name).horizontal_alignment(HorizontalAlignment::Left);
 
         let title_bar = Row::new()
@@ -0,0 +1,84 @@
+//! This module defines the element that is used for giving values to
+//! the variables in roll expressions, eg. `$str`.
+use critfail::Score;
+use iced::{text_input, Align, Column, Element, Length, Row, Text, TextInput};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone)]
+/// Messages that can be sent by the variables box.
+pub(super) enum VariableMsg {
+    /// The value of the variable with this name has been changed.
+    ValueChanged(String, String),
+}
+
+#[derive(Default)]
+struct Variable {
+    /// A text input that holds the value of the variable
+    value_box: text_input::State,
+    value: String,
+}
+
+/// The values of the variables used by the expression boxes. Every
+/// expression that uses a variable shares the same value, so changing
+/// it here (eg. when a character levels up) updates all of them.
+#[derive(Default)]
+pub(super) struct VariablesBox {
+    variables: BTreeMap<String, Variable>,
+}
+
+#[cfg(target_arch = "wasm32")]
+const VARIABLE_SIZE: u16 = 20;
+#[cfg(not(target_arch = "wasm32"))]
+const VARIABLE_SIZE: u16 = 25;
+
+impl VariablesBox {
+    pub(super) fn update(&mut self, message: VariableMsg) {
+        match message {
+            VariableMsg::ValueChanged(name, value) => {
+                self.variables.entry(name).or_default().value = value
+            }
+        }
+    }
+
+    /// Get the value of each variable that has a valid value.
+    pub(super) fn context(&self) -> HashMap<String, Score> {
+        self.variables
+            .iter()
+            .filter_map(|(name, var)| Some((name.clone(), var.value.trim().parse().ok()?)))
+            .collect()
+    }
+
+    /// Show a box to enter the value of each variable in `names`.
+    pub(super) fn view(&mut self, names: &[String]) -> Element<'_, VariableMsg> {
+        for name in names {
+            self.variables.entry(name.clone()).or_default();
+        }
+
+        self.variables
+            .iter_mut()
+            .filter(|(name, _)| names.contains(name))
+            .fold(Column::new().spacing(10), |col, (name, var)| {
+                let msg_name = name.clone();
+                let value_box =
+                    TextInput::new(&mut var.value_box, "Value", &var.value, move |value| {
+                        VariableMsg::ValueChanged(msg_name.clone(), value)
+                    })
+                    .size(VARIABLE_SIZE)
+                    .padding(0)
+                    .width(Length::FillPortion(3));
+
+                col.push(
+                    Row::new()
+                        .spacing(20)
+                        .align_items(Align::Center)
+                        .push(
+                            Text::new(format!("${}", name))
+                                .size(VARIABLE_SIZE)
+                                .width(Length::FillPortion(1)),
+                        )
+                        .push(value_box),
+                )
+            })
+            .into()
+    }
+}
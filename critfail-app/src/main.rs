@@ -14,7 +14,12 @@ fn main() -> Result<(), Box<dyn Error>> {
     {
         let args: Vec<String> = env::args().collect();
         if args.len() > 1 {
-            cli::run_args(args)?;
+            // Errors are printed with Display so that multi-line messages,
+            // like the underlined expression of a parse error, stay readable
+            if let Err(err) = cli::run_args(args) {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
             return Ok(());
         }
     }
@@ -0,0 +1,108 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{ParseError, ParseErrorKind};
+
+/// One of the six ability scores.
+///
+/// Abilities are written as their three letter abbreviation in roll
+/// expressions (eg. the `dex` in `save DC15 dex +3 : 8d6 half`).
+///
+/// ```
+/// use critfail::Ability;
+///
+/// assert_eq!("dex".parse::<Ability>().unwrap(), Ability::Dexterity);
+/// assert_eq!(Ability::Wisdom.to_string(), "wis");
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Ability {
+    /// Strength (`str`).
+    Strength,
+    /// Dexterity (`dex`).
+    Dexterity,
+    /// Constitution (`con`).
+    Constitution,
+    /// Intelligence (`int`).
+    Intelligence,
+    /// Wisdom (`wis`).
+    Wisdom,
+    /// Charisma (`cha`).
+    Charisma,
+}
+
+impl Ability {
+    /// Every ability, in the usual order.
+    pub const ALL: [Ability; 6] = [
+        Ability::Strength,
+        Ability::Dexterity,
+        Ability::Constitution,
+        Ability::Intelligence,
+        Ability::Wisdom,
+        Ability::Charisma,
+    ];
+
+    /// Get the full name of this ability, eg. `Wisdom`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Ability::Strength => "Strength",
+            Ability::Dexterity => "Dexterity",
+            Ability::Constitution => "Constitution",
+            Ability::Intelligence => "Intelligence",
+            Ability::Wisdom => "Wisdom",
+            Ability::Charisma => "Charisma",
+        }
+    }
+
+    /// Get the three letter abbreviation of this ability.
+    pub fn abbreviation(self) -> &'static str {
+        match self {
+            Ability::Strength => "str",
+            Ability::Dexterity => "dex",
+            Ability::Constitution => "con",
+            Ability::Intelligence => "int",
+            Ability::Wisdom => "wis",
+            Ability::Charisma => "cha",
+        }
+    }
+}
+
+impl FromStr for Ability {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ability::ALL
+            .iter()
+            .copied()
+            .find(|a| a.abbreviation() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = Ability::ALL.iter().map(|a| a.abbreviation()).collect();
+                ParseError::whole(ParseErrorKind::UnknownToken, s).expecting(&names)
+            })
+    }
+}
+
+impl fmt::Display for Ability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.abbreviation())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for a in Ability::ALL.iter() {
+            assert_eq!(a.to_string().parse::<Ability>().unwrap(), *a);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        assert!("".parse::<Ability>().is_err());
+        assert!("dexterity".parse::<Ability>().is_err());
+        assert!("luck".parse::<Ability>().is_err());
+    }
+}
@@ -1,8 +1,14 @@
+use rand::Rng;
+use std::collections::HashMap;
+use std::fmt;
+
 use crate::RollExpression;
-use crate::{AdvState, Check, Damage};
+use crate::{util, AdvState, Check, CritRule, Damage, Score, Sides, UnboundVariable};
 
+pub use attackbuilder::AttackBuilder;
 pub use attackoutcome::{AttackOutcome, AttackOutcomeBuilder};
 
+mod attackbuilder;
 mod attackoutcome;
 mod attackparse;
 
@@ -19,12 +25,224 @@ mod attackparse;
 /// print!("{:?}", outcome); // Prints something like "(7)+3 ? [4+5]+4"
 /// ```
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Attack {
     check: Check,
     damage: Damage,
 }
 
 impl Attack {
+    /// Get the attack roll of this attack.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Attack, Check};
+    ///
+    /// let attack = Attack::new("a+5?1d8+3").unwrap();
+    ///
+    /// assert_eq!(attack.check(), &Check::new("a+5").unwrap());
+    /// ```
+    pub fn check(&self) -> &Check {
+        &self.check
+    }
+
+    /// Get the damage this attack deals when it hits.
+    pub fn damage(&self) -> &Damage {
+        &self.damage
+    }
+
+    /// Get the advantage state this attack is rolled with.
+    pub fn advantage(&self) -> AdvState {
+        self.check.advantage()
+    }
+
+    /// Roll this attack with the advantage state `adv`.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, AdvState, Attack};
+    ///
+    /// let attack = Attack::new("r+5?1d8+3").unwrap().with_advantage(AdvState::Advantage);
+    ///
+    /// assert_eq!(attack, Attack::new("a+5?1d8+3").unwrap());
+    /// ```
+    pub fn with_advantage(self, adv: AdvState) -> Self {
+        Self {
+            check: self.check.with_advantage(adv),
+            ..self
+        }
+    }
+
+    /// Add a constant `modifier` to the attack roll.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Attack};
+    ///
+    /// // Archery fighting style
+    /// let attack = Attack::new("r+5?1d8+3").unwrap().with_modifier(2);
+    ///
+    /// assert_eq!(attack, Attack::new("r+5+2?1d8+3").unwrap());
+    /// ```
+    pub fn with_modifier(self, modifier: Score) -> Self {
+        Self {
+            check: self.check.with_modifier(modifier),
+            ..self
+        }
+    }
+
+    /// Add a constant `modifier` to the damage of this attack.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Attack};
+    ///
+    /// // Dueling fighting style
+    /// let attack = Attack::new("r+5?1d8+3").unwrap().with_damage_modifier(2);
+    ///
+    /// assert_eq!(attack, Attack::new("r+5?1d8+3+2").unwrap());
+    /// ```
+    pub fn with_damage_modifier(self, modifier: Score) -> Self {
+        Self {
+            damage: self.damage.with_modifier(modifier),
+            ..self
+        }
+    }
+
+    /// Add `num` dice with `sides` sides to the damage of this attack.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Attack};
+    ///
+    /// // Sneak attack
+    /// let attack = Attack::new("r+7?1d6+4").unwrap().with_damage_dice(3, 6);
+    ///
+    /// assert_eq!(attack, Attack::new("r+7?1d6+4+3d6").unwrap());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num` or `sides` is 0.
+    pub fn with_damage_dice(self, num: u32, sides: Sides) -> Self {
+        Self {
+            damage: self.damage.with_dice(num, sides),
+            ..self
+        }
+    }
+
+    /// Get the lowest d20 roll that counts as a critical hit.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Attack};
+    ///
+    /// assert_eq!(Attack::new("r+5?1d8+3").unwrap().crit_range(), 20);
+    /// assert_eq!(Attack::new("r+5c19?1d8+3").unwrap().crit_range(), 19);
+    /// ```
+    pub fn crit_range(&self) -> Score {
+        self.check.crit_range()
+    }
+
+    /// Make this attack a critical hit when the d20 rolls `crit` or
+    /// higher, like for a Champion fighter's Improved Critical.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Attack};
+    ///
+    /// let attack = Attack::new("r+5?1d8+3").unwrap();
+    /// let champion = attack.clone().with_crit_range(19);
+    ///
+    /// assert_eq!(champion, Attack::new("r+5c19?1d8+3").unwrap());
+    /// assert!(champion.expected_damage(15) > attack.expected_damage(15));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `crit` isn't from 2 to 20.
+    pub fn with_crit_range(self, crit: Score) -> Self {
+        Self {
+            check: self.check.with_crit_range(crit),
+            ..self
+        }
+    }
+
+    /// Get the AC this attack is rolled against, if it has one.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Attack};
+    ///
+    /// assert_eq!(Attack::new("r+7?1d8+4").unwrap().target(), None);
+    /// assert_eq!(Attack::new("r+7?1d8+4 vs AC 16").unwrap().target(), Some(16));
+    /// ```
+    pub fn target(&self) -> Option<Score> {
+        self.check.target()
+    }
+
+    /// Roll this attack against a target with an armor class of `ac`,
+    /// so that its outcome reports whether it hit. A miss deals no
+    /// damage.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Attack};
+    ///
+    /// let attack = Attack::new("r+7?1d8+4").unwrap().with_target(16);
+    ///
+    /// assert_eq!(attack, Attack::new("r+7?1d8+4 vs AC 16").unwrap());
+    ///
+    /// let outcome = attack.roll();
+    /// if outcome.hit() == Some(false) {
+    ///     assert_eq!(outcome.damage().score(), 0);
+    /// }
+    /// ```
+    pub fn with_target(self, ac: Score) -> Self {
+        Self {
+            check: self.check.with_target(ac),
+            ..self
+        }
+    }
+
+    /// Use `rule` to roll the damage of this attack on a critical hit.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Attack, CritRule};
+    ///
+    /// let attack = Attack::new("r+5?1d8+3").unwrap();
+    /// let max_plus_roll = attack.clone().with_crit_rule(CritRule::MaxPlusRoll);
+    ///
+    /// assert!(max_plus_roll.expected_damage(15) > attack.expected_damage(15));
+    /// ```
+    pub fn with_crit_rule(self, rule: CritRule) -> Self {
+        Self {
+            damage: self.damage.with_crit_rule(rule),
+            ..self
+        }
+    }
+
+    /// Get the rule used to roll the damage of this attack on a
+    /// critical hit.
+    pub fn crit_rule(&self) -> &CritRule {
+        self.damage.crit_rule()
+    }
+
+    /// Get the names of the variables in this attack (without the
+    /// `$`), in the order they first appear.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Attack};
+    ///
+    /// let attack = Attack::new("r+$str+$prof?1d8+$str").unwrap();
+    /// assert_eq!(attack.variables(), vec!["str", "prof"]);
+    /// ```
+    pub fn variables(&self) -> Vec<&str> {
+        let mut names = self.check.variables();
+        util::merge_names(&mut names, self.damage.variables());
+        names
+    }
+
+    /// Replace each variable in this attack with its value in
+    /// `context`.
+    pub fn bind(&self, context: &HashMap<String, Score>) -> Result<Self, UnboundVariable> {
+        Ok(Self {
+            check: self.check.bind(context)?,
+            damage: self.damage.bind(context)?,
+        })
+    }
+
     /// Roll this check using `adv` to override the advantage state.
     ///
     /// ```
@@ -36,21 +254,114 @@ impl Attack {
     /// attack.roll_with_advantage(AdvState::Neutral); // Roll without advantage
     /// attack.roll_with_advantage(AdvState::Disadvantage); // Roll with disadvantage
     /// ```
+    ///
+    /// A critical hit rolls damage following the crit rule of the
+    /// attack.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Attack, AdvState, CritRule};
+    /// let attack = Attack::new("r+3?3d6+4").unwrap().with_crit_rule(CritRule::DoubleTotal);
+    ///
+    /// let outcome = attack.roll_with_advantage(AdvState::Advantage);
+    /// ```
     pub fn roll_with_advantage(&self, adv: AdvState) -> AttackOutcome {
-        let check = self.check.roll_with_advantage(adv);
-        let damage = self.damage.roll_with_check(&check);
+        self.roll_with_advantage_and_rng(adv, &mut rand::thread_rng())
+    }
+
+    /// Roll this attack using `adv` to override the advantage state,
+    /// and `rng` as the source of randomness.
+    pub fn roll_with_advantage_and_rng<R: Rng + ?Sized>(
+        &self,
+        adv: AdvState,
+        rng: &mut R,
+    ) -> AttackOutcome {
+        let check = self.check.roll_with_advantage_and_rng(adv, rng);
+        let damage = self.damage.roll_with_score_and_rng(check.crit_score(), rng);
 
         AttackOutcome::new(check, damage)
     }
+
+    /// Calculate the average damage this attack deals against a target
+    /// with an armor class of `ac`.
+    ///
+    /// A critical hit always hits and deals critical damage, a natural 1
+    /// always misses, and a miss deals no damage.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Attack};
+    ///
+    /// let attack = Attack::new("r+5?1d8+3").unwrap();
+    ///
+    /// // 50% chance to hit for 7.5, and a 5% chance to crit for 12
+    /// let expected = attack.expected_damage(15);
+    /// assert!((expected - 4.35).abs() < 1e-9);
+    ///
+    /// // A minimum on the d20 doesn't make it crit more often
+    /// let attack = Attack::new("r+5 min20?1d8").unwrap();
+    /// assert!((attack.expected_damage(30) - 0.45).abs() < 1e-9);
+    /// ```
+    pub fn expected_damage(&self, ac: Score) -> f64 {
+        self.expected_damage_with_advantage(ac, self.check.adv)
+    }
+
+    /// Calculate the average damage this attack deals against a target
+    /// with an armor class of `ac`, using `adv` to override the
+    /// advantage state.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Attack, AdvState};
+    ///
+    /// let attack = Attack::new("r+5?1d8+3").unwrap();
+    ///
+    /// let normal = attack.expected_damage(15);
+    /// let advantage = attack.expected_damage_with_advantage(15, AdvState::Advantage);
+    /// assert!(advantage > normal);
+    /// ```
+    pub fn expected_damage_with_advantage(&self, ac: Score, adv: AdvState) -> f64 {
+        let chance = self.check.chance_with_advantage(ac, adv);
+        let crit = chance.critical();
+        let hit = chance.success_with_crits() - crit;
+
+        let crit_damage = self.damage.crit_distribution();
+
+        hit * self.damage.distribution().mean() + crit * crit_damage.mean()
+    }
 }
 
 impl RollExpression for Attack {
     type Outcome = AttackOutcome;
 
-    fn roll(&self) -> Self::Outcome {
-        let check = self.check.roll();
-        let damage = self.damage.roll_with_check(&check);
+    fn roll_with_rng<R: Rng + ?Sized>(&self, rng: &mut R) -> Self::Outcome {
+        self.roll_with_advantage_and_rng(self.check.adv, rng)
+    }
+}
 
-        AttackOutcome::new(check, damage)
+/// Attacks are written in a canonical form of the notation they are
+/// parsed from, so parsing the written attack gives the same `Attack`
+/// back.
+///
+/// ```
+/// use critfail::{RollExpression, Attack, CritRule};
+///
+/// let attack = Attack::new("+3?2d8-1").unwrap();
+/// assert_eq!(attack.to_string(), "r+3?2d8-1");
+///
+/// let attack = Attack::new("a+7 vs 16 ? 1d8+4 slashing").unwrap();
+/// assert_eq!(attack.to_string(), "a+7?1d8+4 slashing vs AC 16");
+/// assert_eq!(Attack::new(&attack.to_string()).unwrap(), attack);
+///
+/// let attack = Attack::new("r+5?2d6+3 crit max vs AC 15").unwrap();
+/// assert_eq!(attack.crit_rule(), &CritRule::MaxPlusRoll);
+/// assert_eq!(attack.to_string(), "r+5?2d6+3 crit max vs AC 15");
+/// ```
+impl fmt::Display for Attack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.check.write_without_target(f)?;
+        write!(f, "?{}", self.damage)?;
+        if let Some(ac) = self.check.target() {
+            write!(f, " vs AC {}", ac)?;
+        }
+
+        Ok(())
     }
 }
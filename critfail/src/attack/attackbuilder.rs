@@ -0,0 +1,152 @@
+use crate::{AdvState, Attack, CheckBuilder, CritRule, DamageBuilder, DamageType, Score, Sides};
+
+/// This is used to create an `Attack` roll expression without parsing
+/// one from a string.
+///
+/// ```
+/// use critfail::{RollExpression, Attack, AttackBuilder, DamageType};
+///
+/// let attack = AttackBuilder::new()
+///     .check_modifier(7)
+///     .check_target(16)
+///     .damage_dice(1, 8)
+///     .damage_modifier(4)
+///     .damage_type(DamageType::Slashing)
+///     .build();
+///
+/// assert_eq!(attack, Attack::new("r+7?1d8+4 slashing vs AC 16").unwrap());
+/// ```
+#[derive(Default)]
+pub struct AttackBuilder {
+    check: CheckBuilder,
+    damage: DamageBuilder,
+}
+
+impl AttackBuilder {
+    /// Create a new `AttackBuilder`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set the advantage state of the attack roll.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, AdvState, Attack, AttackBuilder};
+    ///
+    /// let attack = AttackBuilder::new()
+    ///     .advantage(AdvState::Disadvantage)
+    ///     .check_modifier(4)
+    ///     .damage_dice(2, 6)
+    ///     .build();
+    ///
+    /// assert_eq!(attack, Attack::new("d+4?2d6").unwrap());
+    /// ```
+    pub fn advantage(self, adv: AdvState) -> Self {
+        Self {
+            check: self.check.advantage(adv),
+            ..self
+        }
+    }
+
+    /// Add a constant modifier to the attack roll. This method can be
+    /// chained multiple times for multiple modifiers.
+    pub fn check_modifier(self, modifier: Score) -> Self {
+        Self {
+            check: self.check.modifier(modifier),
+            ..self
+        }
+    }
+
+    /// Add `num` dice with `sides` sides to the attack roll. The dice
+    /// are subtracted if `sides` is negative.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num` or `sides` is 0.
+    pub fn check_dice(self, num: u32, sides: Sides) -> Self {
+        Self {
+            check: self.check.dice(num, sides),
+            ..self
+        }
+    }
+
+    /// Make the attack a critical hit when the d20 rolls `crit` or
+    /// higher.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Attack, AttackBuilder};
+    ///
+    /// let attack = AttackBuilder::new()
+    ///     .check_modifier(5)
+    ///     .check_crit(19)
+    ///     .damage_dice(1, 10)
+    ///     .build();
+    ///
+    /// assert_eq!(attack, Attack::new("r+5c19?1d10").unwrap());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `crit` isn't from 2 to 20.
+    pub fn check_crit(self, crit: Score) -> Self {
+        Self {
+            check: self.check.crit(crit),
+            ..self
+        }
+    }
+
+    /// Set the AC the attack is rolled against.
+    pub fn check_target(self, ac: Score) -> Self {
+        Self {
+            check: self.check.target(ac),
+            ..self
+        }
+    }
+
+    /// Add a constant modifier to the damage. This method can be
+    /// chained multiple times for multiple modifiers.
+    pub fn damage_modifier(self, modifier: Score) -> Self {
+        Self {
+            damage: self.damage.modifier(modifier),
+            ..self
+        }
+    }
+
+    /// Add `num` dice with `sides` sides to the damage. The dice are
+    /// subtracted if `sides` is negative.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num` or `sides` is 0.
+    pub fn damage_dice(self, num: u32, sides: Sides) -> Self {
+        Self {
+            damage: self.damage.dice(num, sides),
+            ..self
+        }
+    }
+
+    /// Give a damage type to the damage added so far that doesn't have
+    /// a type yet.
+    pub fn damage_type(self, damage_type: DamageType) -> Self {
+        Self {
+            damage: self.damage.damage_type(damage_type),
+            ..self
+        }
+    }
+
+    /// Use `rule` to roll the damage on a critical hit.
+    pub fn crit_rule(self, rule: CritRule) -> Self {
+        Self {
+            damage: self.damage.crit_rule(rule),
+            ..self
+        }
+    }
+
+    /// Create an `Attack` from this builder.
+    pub fn build(self) -> Attack {
+        Attack {
+            check: self.check.build(),
+            damage: self.damage.build(),
+        }
+    }
+}
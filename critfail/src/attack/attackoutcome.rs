@@ -1,6 +1,8 @@
 use std::fmt;
 
-use crate::{CheckOutcome, CheckOutcomeBuilder, DamageOutcome, DamageOutcomeBuilder, Score, Sides};
+use crate::{
+    CheckOutcome, CheckOutcomeBuilder, CritScore, DamageOutcome, DamageOutcomeBuilder, Score, Sides,
+};
 
 /// The outcome of an attack roll.
 ///
@@ -13,6 +15,7 @@ use crate::{CheckOutcome, CheckOutcomeBuilder, DamageOutcome, DamageOutcomeBuild
 /// let outcome: AttackOutcome = Attack::new("r+1?1d12+1").unwrap().roll();
 /// ```
 #[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AttackOutcome {
     check: CheckOutcome,
     damage: DamageOutcome,
@@ -20,9 +23,42 @@ pub struct AttackOutcome {
 
 impl AttackOutcome {
     pub(crate) fn new(check: CheckOutcome, damage: DamageOutcome) -> Self {
+        // A miss doesn't deal any damage. Without an AC the damage of a
+        // natural 1 is kept so that it can still be shown.
+        let damage = if check.success() == Some(false) {
+            DamageOutcome::new(vec![])
+        } else {
+            damage
+        };
+
         AttackOutcome { check, damage }
     }
 
+    /// Get whether this attack hit, or `None` if it wasn't rolled
+    /// against an AC.
+    ///
+    /// A critical hit always hits and a critical failure always misses.
+    ///
+    /// ```
+    /// use critfail::AttackOutcomeBuilder;
+    ///
+    /// // To create a result that could come from rolling 'r+4?2d6 vs AC 15'
+    /// let outcome = AttackOutcomeBuilder::new()
+    ///     .check(8)
+    ///     .check_modifier(4)
+    ///     .check_target(15)
+    ///     .damage_dice(6, vec![3, 4])
+    ///     .build();
+    ///
+    /// assert_eq!(outcome.hit(), Some(false));
+    /// assert_eq!(outcome.damage().score(), 0);
+    /// assert_eq!(format!("{}", outcome), "Miss (12)");
+    /// assert_eq!(format!("{:?}", outcome), "(8)+4 vs 15 ? Miss");
+    /// ```
+    pub fn hit(&self) -> Option<bool> {
+        self.check.success()
+    }
+
     /// Get the check portion of this `AttackOutcome`.
     pub fn check(&self) -> &CheckOutcome {
         &self.check
@@ -36,13 +72,24 @@ impl AttackOutcome {
 
 impl fmt::Display for AttackOutcome {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{} ? {}", self.check, self.damage)
+        match (self.check.crit_score(), self.hit()) {
+            (_, None) => write!(f, "{} ? {}", self.check, self.damage),
+            (CritScore::Critical, Some(_)) => write!(f, "Critical ? {}", self.damage),
+            (CritScore::Fail, Some(_)) => write!(f, "Fail"),
+            (CritScore::Normal(score), Some(true)) => {
+                write!(f, "Hit ({}) ? {}", score, self.damage)
+            }
+            (CritScore::Normal(score), Some(false)) => write!(f, "Miss ({})", score),
+        }
     }
 }
 
 impl fmt::Debug for AttackOutcome {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:?} ? {:?}", self.check, self.damage)
+        match self.hit() {
+            Some(false) => write!(f, "{:?} ? Miss", self.check),
+            _ => write!(f, "{:?} ? {:?}", self.check, self.damage),
+        }
     }
 }
 
@@ -151,6 +198,81 @@ impl AttackOutcomeBuilder {
         }
     }
 
+    /// Set a minimum value for the d20 of the check part of this attack
+    /// roll.
+    ///
+    /// ```
+    /// use critfail::AttackOutcomeBuilder;
+    ///
+    /// // To create a result that could come from rolling 'r+2 min10?2d8'
+    /// let outcome = AttackOutcomeBuilder::new()
+    ///     .check(6)
+    ///     .check_min(10)
+    ///     .check_modifier(2)
+    ///     .damage_dice(8, vec![4, 5])
+    ///     .build();
+    ///
+    /// assert_eq!(outcome.check().score(), 12);
+    /// assert_eq!(
+    ///     format!("{:?}", outcome),
+    ///     "(6→10)+2 ? [4+5]"
+    /// );
+    /// ```
+    pub fn check_min(self, min: Score) -> Self {
+        Self {
+            check: self.check.min(min),
+            ..self
+        }
+    }
+
+    /// Set the lowest d20 roll that counts as a critical hit for the
+    /// check part of this attack roll.
+    ///
+    /// ```
+    /// use critfail::AttackOutcomeBuilder;
+    ///
+    /// // To create a result that could come from rolling 'r+5c19?2d8'
+    /// let outcome = AttackOutcomeBuilder::new()
+    ///     .check(19)
+    ///     .check_crit(19)
+    ///     .check_modifier(5)
+    ///     .damage_dice(8, vec![4, 5])
+    ///     .damage_dice(8, vec![1, 7])
+    ///     .build();
+    ///
+    /// assert_eq!(format!("{}", outcome), "Critical ? 17");
+    /// ```
+    pub fn check_crit(self, crit: Score) -> Self {
+        Self {
+            check: self.check.crit(crit),
+            ..self
+        }
+    }
+
+    /// Set the AC that the attack was rolled against.
+    ///
+    /// ```
+    /// use critfail::AttackOutcomeBuilder;
+    ///
+    /// // To create a result that could come from rolling 'r+5?2d8 vs AC 14'
+    /// let outcome = AttackOutcomeBuilder::new()
+    ///     .check(11)
+    ///     .check_modifier(5)
+    ///     .check_target(14)
+    ///     .damage_dice(8, vec![4, 5])
+    ///     .build();
+    ///
+    /// assert_eq!(outcome.hit(), Some(true));
+    /// assert_eq!(format!("{}", outcome), "Hit (16) ? 9");
+    /// assert_eq!(format!("{:?}", outcome), "(11)+5 vs 14 ? [4+5]");
+    /// ```
+    pub fn check_target(self, ac: Score) -> Self {
+        Self {
+            check: self.check.target(ac),
+            ..self
+        }
+    }
+
     /// Add a constant modifier to the check part of this attack roll.
     /// This method can be chained multiple times for multiple
     /// modifiers.
@@ -311,6 +433,43 @@ mod tests {
         assert_eq!(format!("{:?}", r), "(20/4)+3 ? [2+6+8]+[1+5+2]-2");
     }
 
+    #[test]
+    fn targeted() {
+        let damage = || DamageOutcome::new(vec![D(8, vec![2, 6]), M(3)]);
+
+        let hit = AttackOutcome::new(
+            CheckOutcome::new(Neutral, 12, 4, vec![M(5)]).with_target(Some(16)),
+            damage(),
+        );
+        assert_eq!(hit.hit(), Some(true));
+        assert_eq!(format!("{}", hit), "Hit (17) ? 11");
+        assert_eq!(format!("{:?}", hit), "(12)+5 vs 16 ? [2+6]+3");
+
+        let miss = AttackOutcome::new(
+            CheckOutcome::new(Neutral, 10, 4, vec![M(5)]).with_target(Some(16)),
+            damage(),
+        );
+        assert_eq!(miss.hit(), Some(false));
+        assert_eq!(miss.damage().score(), 0);
+        assert_eq!(format!("{}", miss), "Miss (15)");
+        assert_eq!(format!("{:?}", miss), "(10)+5 vs 16 ? Miss");
+
+        let critical = AttackOutcome::new(
+            CheckOutcome::new(Neutral, 20, 4, vec![M(-5)]).with_target(Some(30)),
+            damage(),
+        );
+        assert_eq!(critical.hit(), Some(true));
+        assert_eq!(format!("{}", critical), "Critical ? 11");
+
+        let fail = AttackOutcome::new(
+            CheckOutcome::new(Neutral, 1, 4, vec![M(20)]).with_target(Some(10)),
+            damage(),
+        );
+        assert_eq!(fail.hit(), Some(false));
+        assert_eq!(format!("{}", fail), "Fail");
+        assert_eq!(format!("{:?}", fail), "(1)+20 vs 10 ? Miss");
+    }
+
     #[test]
     fn critfail() {
         let r = AttackOutcome::new(
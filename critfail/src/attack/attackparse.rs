@@ -1,24 +1,22 @@
+use crate::parse::{self, AttackExpr};
 use crate::Attack;
 use crate::ParseError;
-use regex::Regex;
 
 use std::str::FromStr;
 
-lazy_static! {
-    static ref ATTACK_RE: Regex = Regex::new(r"^([^?]+) *\? *([^?]+)$").unwrap();
-}
-
 impl FromStr for Attack {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some(cap) = ATTACK_RE.captures(s) {
-            let check = cap[1].parse()?;
-            let damage = cap[2].parse()?;
+        Ok(parse::attack(s)?.into())
+    }
+}
 
-            Ok(Attack { check, damage })
-        } else {
-            Err(ParseError::new(s))
+impl From<AttackExpr> for Attack {
+    fn from(expr: AttackExpr) -> Self {
+        Attack {
+            check: expr.check.into(),
+            damage: expr.damage.into(),
         }
     }
 }
@@ -73,6 +71,48 @@ mod tests {
         )
     }
 
+    #[test]
+    fn crit_range() {
+        assert_eq!(
+            "r+5c19?1d8+3".parse::<Attack>().unwrap(),
+            Attack {
+                check: Check::new("r+5").unwrap().with_crit_range(19),
+                damage: Damage::new("1d8+3").unwrap()
+            }
+        )
+    }
+
+    #[test]
+    fn target() {
+        for s in &[
+            "r+7?1d8+4 vs AC 16",
+            "r+7 ? 1d8+4 vs 16",
+            "r+7?1d8+4 vs ac16",
+        ] {
+            assert_eq!(
+                s.parse::<Attack>().unwrap(),
+                Attack {
+                    check: Check::new("r+7").unwrap().with_target(16),
+                    damage: Damage::new("1d8+4").unwrap()
+                }
+            )
+        }
+
+        assert!("r+7?1d8+4 vs".parse::<Attack>().is_err());
+        assert!("r+7?1d8+4 vs AC".parse::<Attack>().is_err());
+    }
+
+    #[test]
+    fn min() {
+        assert_eq!(
+            "r+7 min10 ? 1d6+4".parse::<Attack>().unwrap(),
+            Attack {
+                check: Check::new("r+7 min10").unwrap(),
+                damage: Damage::new("1d6+4").unwrap()
+            }
+        )
+    }
+
     #[test]
     fn invalid() {
         assert!("r+3".parse::<Attack>().is_err());
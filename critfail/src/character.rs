@@ -0,0 +1,358 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{Ability, Check, Score};
+
+pub use skill::Skill;
+
+mod skill;
+
+/// How much of a character's proficiency bonus is added to a check.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Proficiency {
+    /// The proficiency bonus isn't added.
+    #[default]
+    None,
+    /// Half the proficiency bonus is added, rounded down (eg. from Jack
+    /// of All Trades).
+    Half,
+    /// The proficiency bonus is added.
+    Proficient,
+    /// Double the proficiency bonus is added.
+    Expertise,
+}
+
+impl Proficiency {
+    /// Get the amount this adds to a check for a character with a
+    /// proficiency bonus of `proficiency_bonus`.
+    ///
+    /// ```
+    /// use critfail::Proficiency;
+    ///
+    /// assert_eq!(Proficiency::Half.bonus(3), 1);
+    /// assert_eq!(Proficiency::Expertise.bonus(3), 6);
+    /// ```
+    pub fn bonus(self, proficiency_bonus: Score) -> Score {
+        match self {
+            Proficiency::None => 0,
+            Proficiency::Half => proficiency_bonus.div_euclid(2),
+            Proficiency::Proficient => proficiency_bonus,
+            Proficiency::Expertise => proficiency_bonus * 2,
+        }
+    }
+}
+
+/// The parts of a character sheet that are used to make ability
+/// checks, skill checks and saving throws.
+///
+/// A new character has a score of 10 in every ability, a proficiency
+/// bonus of +2, and no proficiencies.
+///
+/// ```
+/// use critfail::{Ability, Character, Check, RollExpression, Skill};
+///
+/// let rogue = Character::new()
+///     .with_level(5)
+///     .with_score(Ability::Dexterity, 18)
+///     .with_score(Ability::Wisdom, 13)
+///     .with_expertise(Skill::Stealth)
+///     .with_skill(Skill::Perception)
+///     .with_save(Ability::Dexterity);
+///
+/// assert_eq!(rogue.skill_check(Skill::Stealth), Check::new("r+10").unwrap());
+/// assert_eq!(rogue.skill_check(Skill::Perception), Check::new("r+4").unwrap());
+/// assert_eq!(rogue.save(Ability::Wisdom), Check::new("r+1").unwrap());
+///
+/// print!("{:?}", rogue.save(Ability::Dexterity).roll()); // Prints something like "(12)+7"
+/// ```
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Character {
+    /// The ability scores, in the order of `Ability::ALL`.
+    scores: [Score; 6],
+    proficiency_bonus: Score,
+    skills: BTreeMap<Skill, Proficiency>,
+    saves: BTreeSet<Ability>,
+    /// Whether half the proficiency bonus is added to ability checks
+    /// that aren't proficient.
+    jack_of_all_trades: bool,
+}
+
+impl Default for Character {
+    fn default() -> Self {
+        Self {
+            scores: [10; 6],
+            proficiency_bonus: 2,
+            skills: BTreeMap::new(),
+            saves: BTreeSet::new(),
+            jack_of_all_trades: false,
+        }
+    }
+}
+
+impl Character {
+    /// Create a character with a score of 10 in every ability, a
+    /// proficiency bonus of +2, and no proficiencies.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the score of `ability`.
+    pub fn with_score(mut self, ability: Ability, score: Score) -> Self {
+        self.scores[ability as usize] = score;
+        self
+    }
+
+    /// Set the proficiency bonus.
+    pub fn with_proficiency_bonus(self, proficiency_bonus: Score) -> Self {
+        Self {
+            proficiency_bonus,
+            ..self
+        }
+    }
+
+    /// Set the proficiency bonus to the bonus for a character of
+    /// `level`.
+    ///
+    /// ```
+    /// use critfail::Character;
+    ///
+    /// assert_eq!(Character::new().with_level(4).proficiency_bonus(), 2);
+    /// assert_eq!(Character::new().with_level(5).proficiency_bonus(), 3);
+    /// assert_eq!(Character::new().with_level(17).proficiency_bonus(), 6);
+    /// ```
+    pub fn with_level(self, level: Score) -> Self {
+        self.with_proficiency_bonus((level.max(1) - 1) / 4 + 2)
+    }
+
+    /// Make the character proficient in `skill`.
+    pub fn with_skill(mut self, skill: Skill) -> Self {
+        self.skills.insert(skill, Proficiency::Proficient);
+        self
+    }
+
+    /// Give the character expertise in `skill`, which doubles their
+    /// proficiency bonus for it.
+    pub fn with_expertise(mut self, skill: Skill) -> Self {
+        self.skills.insert(skill, Proficiency::Expertise);
+        self
+    }
+
+    /// Make the character proficient in saving throws for `ability`.
+    pub fn with_save(mut self, ability: Ability) -> Self {
+        self.saves.insert(ability);
+        self
+    }
+
+    /// Give the character Jack of All Trades, which adds half their
+    /// proficiency bonus (rounded down) to any ability check they
+    /// aren't proficient in. It doesn't apply to saving throws.
+    ///
+    /// ```
+    /// use critfail::{Ability, Character, Check, RollExpression, Skill};
+    ///
+    /// let bard = Character::new()
+    ///     .with_proficiency_bonus(3)
+    ///     .with_jack_of_all_trades();
+    ///
+    /// assert_eq!(bard.skill_check(Skill::Arcana), Check::new("r+1").unwrap());
+    /// assert_eq!(bard.save(Ability::Intelligence), Check::new("r").unwrap());
+    /// ```
+    pub fn with_jack_of_all_trades(self) -> Self {
+        Self {
+            jack_of_all_trades: true,
+            ..self
+        }
+    }
+
+    /// Get the score of `ability`.
+    pub fn score(&self, ability: Ability) -> Score {
+        self.scores[ability as usize]
+    }
+
+    /// Get the modifier of `ability`, which is half of the score minus
+    /// 10, rounded down.
+    ///
+    /// ```
+    /// use critfail::{Ability, Character};
+    ///
+    /// let character = Character::new()
+    ///     .with_score(Ability::Strength, 15)
+    ///     .with_score(Ability::Charisma, 7);
+    ///
+    /// assert_eq!(character.modifier(Ability::Strength), 2);
+    /// assert_eq!(character.modifier(Ability::Charisma), -2);
+    /// ```
+    pub fn modifier(&self, ability: Ability) -> Score {
+        (self.score(ability) - 10).div_euclid(2)
+    }
+
+    /// Get the proficiency bonus.
+    pub fn proficiency_bonus(&self) -> Score {
+        self.proficiency_bonus
+    }
+
+    /// Get how proficient the character is in `skill`, including half
+    /// proficiency from Jack of All Trades.
+    pub fn skill_proficiency(&self, skill: Skill) -> Proficiency {
+        self.skills
+            .get(&skill)
+            .copied()
+            .unwrap_or_else(|| self.unskilled())
+    }
+
+    /// Get how proficient the character is in saving throws for
+    /// `ability`.
+    pub fn save_proficiency(&self, ability: Ability) -> Proficiency {
+        if self.saves.contains(&ability) {
+            Proficiency::Proficient
+        } else {
+            Proficiency::None
+        }
+    }
+
+    /// Get the proficiency for ability checks that don't use a skill
+    /// the character is proficient in.
+    fn unskilled(&self) -> Proficiency {
+        if self.jack_of_all_trades {
+            Proficiency::Half
+        } else {
+            Proficiency::None
+        }
+    }
+
+    /// Get the check for a plain ability check, eg. a Strength check.
+    /// This is also used for initiative, which is a Dexterity check.
+    pub fn check(&self, ability: Ability) -> Check {
+        let bonus = self.unskilled().bonus(self.proficiency_bonus);
+        Check::from_modifier(self.modifier(ability) + bonus)
+    }
+
+    /// Get the check for an ability check using `skill`, eg. a Stealth
+    /// check.
+    pub fn skill_check(&self, skill: Skill) -> Check {
+        let bonus = self.skill_proficiency(skill).bonus(self.proficiency_bonus);
+        Check::from_modifier(self.modifier(skill.ability()) + bonus)
+    }
+
+    /// Get the check for a saving throw for `ability`, eg. a Wisdom
+    /// save.
+    pub fn save(&self, ability: Ability) -> Check {
+        let bonus = self.save_proficiency(ability).bonus(self.proficiency_bonus);
+        Check::from_modifier(self.modifier(ability) + bonus)
+    }
+
+    /// Get every check the character can make, with a name for each
+    /// (eg. "Stealth check" or "Wisdom save"). The ability checks come
+    /// first, then the skill checks, then the saving throws.
+    ///
+    /// ```
+    /// use critfail::{Character, Check, RollExpression};
+    ///
+    /// let checks = Character::new().checks();
+    ///
+    /// assert_eq!(checks.len(), 30);
+    /// assert_eq!(checks[0], ("Strength check".to_string(), Check::new("r").unwrap()));
+    /// assert_eq!(checks[29].0, "Charisma save");
+    /// ```
+    pub fn checks(&self) -> Vec<(String, Check)> {
+        let abilities = Ability::ALL
+            .iter()
+            .map(|&a| (format!("{} check", a.name()), self.check(a)));
+        let skills = Skill::ALL
+            .iter()
+            .map(|&s| (format!("{} check", s.name()), self.skill_check(s)));
+        let saves = Ability::ALL
+            .iter()
+            .map(|&a| (format!("{} save", a.name()), self.save(a)));
+
+        abilities.chain(skills).chain(saves).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RollExpression;
+
+    fn check(s: &str) -> Check {
+        Check::new(s).unwrap()
+    }
+
+    #[test]
+    fn modifiers() {
+        let scores = [
+            (1, -5),
+            (8, -1),
+            (9, -1),
+            (10, 0),
+            (11, 0),
+            (12, 1),
+            (20, 5),
+        ];
+        for &(score, modifier) in scores.iter() {
+            let c = Character::new().with_score(Ability::Dexterity, score);
+            assert_eq!(c.modifier(Ability::Dexterity), modifier, "{}", score);
+        }
+    }
+
+    #[test]
+    fn skills() {
+        let c = Character::new()
+            .with_proficiency_bonus(3)
+            .with_score(Ability::Dexterity, 16)
+            .with_score(Ability::Intelligence, 8)
+            .with_skill(Skill::Acrobatics)
+            .with_expertise(Skill::Stealth)
+            .with_skill(Skill::Arcana);
+
+        assert_eq!(c.skill_check(Skill::Acrobatics), check("r+6"));
+        assert_eq!(c.skill_check(Skill::Stealth), check("r+9"));
+        assert_eq!(c.skill_check(Skill::SleightOfHand), check("r+3"));
+        assert_eq!(c.skill_check(Skill::Arcana), check("r+2"));
+        assert_eq!(c.skill_check(Skill::History), check("r-1"));
+        assert_eq!(c.check(Ability::Dexterity), check("r+3"));
+    }
+
+    #[test]
+    fn saves() {
+        let c = Character::new()
+            .with_level(9)
+            .with_score(Ability::Wisdom, 14)
+            .with_save(Ability::Wisdom)
+            .with_skill(Skill::Insight);
+
+        assert_eq!(c.save(Ability::Wisdom), check("r+6"));
+        assert_eq!(c.save(Ability::Charisma), check("r"));
+        assert_eq!(c.save_proficiency(Ability::Wisdom), Proficiency::Proficient);
+        assert_eq!(c.save_proficiency(Ability::Strength), Proficiency::None);
+    }
+
+    #[test]
+    fn jack_of_all_trades() {
+        let c = Character::new()
+            .with_level(5)
+            .with_score(Ability::Charisma, 18)
+            .with_skill(Skill::Performance)
+            .with_jack_of_all_trades();
+
+        // Half of +3 rounds down to +1
+        assert_eq!(c.skill_proficiency(Skill::Deception), Proficiency::Half);
+        assert_eq!(c.skill_check(Skill::Deception), check("r+5"));
+        assert_eq!(c.skill_check(Skill::Performance), check("r+7"));
+        assert_eq!(c.check(Ability::Dexterity), check("r+1"));
+        assert_eq!(c.save(Ability::Charisma), check("r+4"));
+    }
+
+    #[test]
+    fn checks() {
+        let c = Character::new()
+            .with_score(Ability::Wisdom, 16)
+            .with_save(Ability::Wisdom);
+        let checks = c.checks();
+
+        assert!(checks.contains(&("Wisdom save".to_string(), check("r+5"))));
+        assert!(checks.contains(&("Stealth check".to_string(), check("r"))));
+        assert!(checks.contains(&("Animal Handling check".to_string(), check("r+3"))));
+    }
+}
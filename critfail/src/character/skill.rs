@@ -0,0 +1,127 @@
+use std::fmt;
+
+use crate::Ability;
+
+/// One of the skills that a character can be proficient in.
+///
+/// ```
+/// use critfail::{Ability, Skill};
+///
+/// assert_eq!(Skill::Stealth.ability(), Ability::Dexterity);
+/// assert_eq!(Skill::SleightOfHand.to_string(), "Sleight of Hand");
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Skill {
+    /// Acrobatics (Dexterity).
+    Acrobatics,
+    /// Animal Handling (Wisdom).
+    AnimalHandling,
+    /// Arcana (Intelligence).
+    Arcana,
+    /// Athletics (Strength).
+    Athletics,
+    /// Deception (Charisma).
+    Deception,
+    /// History (Intelligence).
+    History,
+    /// Insight (Wisdom).
+    Insight,
+    /// Intimidation (Charisma).
+    Intimidation,
+    /// Investigation (Intelligence).
+    Investigation,
+    /// Medicine (Wisdom).
+    Medicine,
+    /// Nature (Intelligence).
+    Nature,
+    /// Perception (Wisdom).
+    Perception,
+    /// Performance (Charisma).
+    Performance,
+    /// Persuasion (Charisma).
+    Persuasion,
+    /// Religion (Intelligence).
+    Religion,
+    /// Sleight of Hand (Dexterity).
+    SleightOfHand,
+    /// Stealth (Dexterity).
+    Stealth,
+    /// Survival (Wisdom).
+    Survival,
+}
+
+impl Skill {
+    /// Every skill, in alphabetical order.
+    pub const ALL: [Skill; 18] = [
+        Skill::Acrobatics,
+        Skill::AnimalHandling,
+        Skill::Arcana,
+        Skill::Athletics,
+        Skill::Deception,
+        Skill::History,
+        Skill::Insight,
+        Skill::Intimidation,
+        Skill::Investigation,
+        Skill::Medicine,
+        Skill::Nature,
+        Skill::Perception,
+        Skill::Performance,
+        Skill::Persuasion,
+        Skill::Religion,
+        Skill::SleightOfHand,
+        Skill::Stealth,
+        Skill::Survival,
+    ];
+
+    /// Get the ability that checks with this skill use.
+    pub fn ability(self) -> Ability {
+        match self {
+            Skill::Athletics => Ability::Strength,
+            Skill::Acrobatics | Skill::SleightOfHand | Skill::Stealth => Ability::Dexterity,
+            Skill::Arcana
+            | Skill::History
+            | Skill::Investigation
+            | Skill::Nature
+            | Skill::Religion => Ability::Intelligence,
+            Skill::AnimalHandling
+            | Skill::Insight
+            | Skill::Medicine
+            | Skill::Perception
+            | Skill::Survival => Ability::Wisdom,
+            Skill::Deception | Skill::Intimidation | Skill::Performance | Skill::Persuasion => {
+                Ability::Charisma
+            }
+        }
+    }
+
+    /// Get the name of this skill, eg. `Sleight of Hand`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Skill::Acrobatics => "Acrobatics",
+            Skill::AnimalHandling => "Animal Handling",
+            Skill::Arcana => "Arcana",
+            Skill::Athletics => "Athletics",
+            Skill::Deception => "Deception",
+            Skill::History => "History",
+            Skill::Insight => "Insight",
+            Skill::Intimidation => "Intimidation",
+            Skill::Investigation => "Investigation",
+            Skill::Medicine => "Medicine",
+            Skill::Nature => "Nature",
+            Skill::Perception => "Perception",
+            Skill::Performance => "Performance",
+            Skill::Persuasion => "Persuasion",
+            Skill::Religion => "Religion",
+            Skill::SleightOfHand => "Sleight of Hand",
+            Skill::Stealth => "Stealth",
+            Skill::Survival => "Survival",
+        }
+    }
+}
+
+impl fmt::Display for Skill {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
@@ -1,30 +1,40 @@
 use rand::Rng;
+use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::RangeInclusive;
 
-use crate::Damage;
+use crate::modifier::{self, Modifier};
 use crate::RollExpression;
+use crate::{Distribution, Score, Sides, UnboundVariable};
 
+pub use checkbuilder::CheckBuilder;
 pub use checkoutcome::{CheckOutcome, CheckOutcomeBuilder, CritScore};
 
+mod checkbuilder;
 mod checkoutcome;
 mod checkparse;
 
+/// The d20 rolls that can be the lowest roll of a crit range.
+pub(crate) const CRIT_RANGES: RangeInclusive<Score> = 2..=20;
+
+/// The minimums that the d20 of a check can be given.
+pub(crate) const D20_MINIMUMS: RangeInclusive<Score> = 0..=20;
+
 /// The advantage state of an ability check.
-#[derive(PartialEq, Debug, Clone, Copy)]
+#[derive(PartialEq, Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AdvState {
     /// Check rolled with advantage (roll twice, take the higher value).
     Advantage,
     /// Check rolled with no advantage (only roll once).
+    #[default]
     Neutral,
     /// Check rolled with disadvantage (roll twice, take the lower value).
     Disadvantage,
 }
 
-impl Default for AdvState {
-    fn default() -> Self {
-        Self::Neutral
-    }
-}
-
 /// An ability check - roll a d20, potentially with modifiers or
 /// advantage.
 ///
@@ -38,14 +48,266 @@ impl Default for AdvState {
 /// print!("{}", outcome);   // Prints something like "16"
 /// print!("{:?}", outcome); // Prints something like "(12)+4"
 /// ```
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "CheckFields"))]
 pub struct Check {
+    pub(crate) adv: AdvState,
+    modifiers: Vec<Modifier>,
+    /// The d20 counts as this value if it rolls lower (eg. `r+7 min10`
+    /// for Reliable Talent).
+    min: Option<Score>,
+    /// The lowest d20 roll that counts as a critical success (eg.
+    /// `r+5c19`). A critical success happens on a 20 if this isn't set.
+    crit: Option<Score>,
+    /// The DC (or AC) the check needs to meet or beat to succeed (eg.
+    /// `r+5 vs 15`).
+    target: Option<Score>,
+}
+
+/// The fields of a deserialized `Check`, before its crit range and
+/// minimum are checked.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct CheckFields {
     adv: AdvState,
-    // TODO: the modifier should be a Vec<Modifier> instead of a Damage
-    modifier: Damage,
+    modifiers: Vec<Modifier>,
+    min: Option<Score>,
+    crit: Option<Score>,
+    target: Option<Score>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<CheckFields> for Check {
+    type Error = &'static str;
+
+    fn try_from(fields: CheckFields) -> Result<Self, Self::Error> {
+        if fields.crit.is_some_and(|crit| !CRIT_RANGES.contains(&crit)) {
+            return Err("crit range must be from 2 to 20");
+        }
+        if fields.min.is_some_and(|min| !D20_MINIMUMS.contains(&min)) {
+            return Err("the d20's minimum must be from 0 to 20");
+        }
+
+        Ok(Check {
+            adv: fields.adv,
+            modifiers: fields.modifiers,
+            min: fields.min,
+            crit: fields.crit,
+            target: fields.target,
+        })
+    }
 }
 
 impl Check {
+    /// Create a check that adds a constant `modifier` to the d20.
+    pub(crate) fn from_modifier(modifier: Score) -> Self {
+        let modifiers = match modifier {
+            0 => vec![],
+            m => vec![Modifier::from_constant(m)],
+        };
+
+        Self {
+            modifiers,
+            ..Default::default()
+        }
+    }
+
+    /// Get the lowest d20 roll that counts as a critical success.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Check};
+    ///
+    /// assert_eq!(Check::new("r+5").unwrap().crit_range(), 20);
+    /// assert_eq!(Check::new("r+5c19").unwrap().crit_range(), 19);
+    /// ```
+    pub fn crit_range(&self) -> Score {
+        self.crit.unwrap_or(20)
+    }
+
+    /// Make this check a critical success when the d20 rolls `crit` or
+    /// higher.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Check};
+    ///
+    /// let check = Check::new("r+5").unwrap().with_crit_range(18);
+    ///
+    /// assert_eq!(check, Check::new("r+5c18").unwrap());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `crit` isn't from 2 to 20.
+    pub fn with_crit_range(self, crit: Score) -> Self {
+        assert!(
+            CRIT_RANGES.contains(&crit),
+            "crit range must be from 2 to 20"
+        );
+        Self {
+            crit: Some(crit),
+            ..self
+        }
+    }
+
+    /// Get the DC (or AC) this check is rolled against, if it has one.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Check};
+    ///
+    /// assert_eq!(Check::new("r+5").unwrap().target(), None);
+    /// assert_eq!(Check::new("r+5 vs 15").unwrap().target(), Some(15));
+    /// ```
+    pub fn target(&self) -> Option<Score> {
+        self.target
+    }
+
+    /// Roll this check against `target`, so that its outcome reports
+    /// whether it succeeded.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Check};
+    ///
+    /// let check = Check::new("r+5").unwrap().with_target(15);
+    ///
+    /// assert_eq!(check, Check::new("r+5 vs 15").unwrap());
+    /// assert!(check.roll().success().is_some());
+    /// ```
+    pub fn with_target(self, target: Score) -> Self {
+        Self {
+            target: Some(target),
+            ..self
+        }
+    }
+
+    /// Get the advantage state this check is rolled with.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, AdvState, Check};
+    ///
+    /// assert_eq!(Check::new("a+2").unwrap().advantage(), AdvState::Advantage);
+    /// ```
+    pub fn advantage(&self) -> AdvState {
+        self.adv
+    }
+
+    /// Get the modifiers that are added to the d20, in the order they
+    /// were written.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Check, Modifier};
+    ///
+    /// let check = Check::new("r+5-1d4").unwrap();
+    ///
+    /// assert_eq!(check.modifiers(), &[Modifier::from_constant(5), Modifier::from_dice(1, -4)]);
+    /// assert_eq!(check.modifiers()[1].to_string(), "-1d4");
+    /// ```
+    pub fn modifiers(&self) -> &[Modifier] {
+        &self.modifiers
+    }
+
+    /// Roll this check with the advantage state `adv`.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, AdvState, Check};
+    ///
+    /// let check = Check::new("r+5").unwrap().with_advantage(AdvState::Disadvantage);
+    ///
+    /// assert_eq!(check, Check::new("d+5").unwrap());
+    /// ```
+    pub fn with_advantage(self, adv: AdvState) -> Self {
+        Self { adv, ..self }
+    }
+
+    /// Add a constant `modifier` to the d20, eg. the bonus from a magic
+    /// item.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Check};
+    ///
+    /// let check = Check::new("r+5").unwrap().with_modifier(2);
+    ///
+    /// assert_eq!(check, Check::new("r+5+2").unwrap());
+    /// ```
+    pub fn with_modifier(mut self, modifier: Score) -> Self {
+        self.modifiers.push(Modifier::from_constant(modifier));
+        self
+    }
+
+    /// Add `num` dice with `sides` sides to the d20. The dice are
+    /// subtracted if `sides` is negative.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Check};
+    ///
+    /// let blessed = Check::new("r+5").unwrap().with_dice(1, 4);
+    /// let baned = Check::new("r+5").unwrap().with_dice(1, -4);
+    ///
+    /// assert_eq!(blessed, Check::new("r+5+1d4").unwrap());
+    /// assert_eq!(baned, Check::new("r+5-1d4").unwrap());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num` or `sides` is 0.
+    pub fn with_dice(mut self, num: u32, sides: Sides) -> Self {
+        self.modifiers.push(Modifier::from_dice(num, sides));
+        self
+    }
+
+    /// Make the d20 count as `min` if it rolls lower, like for a
+    /// rogue's Reliable Talent.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Check};
+    ///
+    /// let check = Check::new("r+7").unwrap().with_min(10);
+    ///
+    /// assert_eq!(check, Check::new("r+7 min10").unwrap());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` isn't from 0 to 20.
+    pub fn with_min(self, min: Score) -> Self {
+        assert!(
+            D20_MINIMUMS.contains(&min),
+            "the d20's minimum must be from 0 to 20"
+        );
+        Self {
+            min: Some(min),
+            ..self
+        }
+    }
+
+    /// Get the names of the variables in the modifier of this check
+    /// (without the `$`), in the order they first appear.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Check};
+    ///
+    /// assert_eq!(Check::new("r+$dex+$prof").unwrap().variables(), vec!["dex", "prof"]);
+    /// ```
+    pub fn variables(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        for modifier in &self.modifiers {
+            modifier.add_variables(&mut names);
+        }
+        names
+    }
+
+    /// Replace each variable in this check with its value in `context`.
+    pub fn bind(&self, context: &HashMap<String, Score>) -> Result<Self, UnboundVariable> {
+        Ok(Self {
+            modifiers: self
+                .modifiers
+                .iter()
+                .map(|modifier| modifier.bind(context))
+                .collect::<Result<_, _>>()?,
+            ..self.clone()
+        })
+    }
+
     /// Roll this check using `adv` to override the advantage state.
     ///
     /// ```
@@ -58,17 +320,306 @@ impl Check {
     /// check.roll_with_advantage(AdvState::Disadvantage); // Roll with disadvantage
     /// ```
     pub fn roll_with_advantage(&self, adv: AdvState) -> CheckOutcome {
-        let r1 = rand::thread_rng().gen_range(1, 21);
-        let r2 = rand::thread_rng().gen_range(1, 21);
-        let mods = self.modifier.roll();
-        CheckOutcome::new(adv, r1, r2, mods.into_modifiers().into_inner())
+        self.roll_with_advantage_and_rng(adv, &mut rand::thread_rng())
+    }
+
+    /// Roll this check using `adv` to override the advantage state, and
+    /// `rng` as the source of randomness.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Check, AdvState};
+    /// use rand::{rngs::StdRng, SeedableRng};
+    ///
+    /// let check = Check::new("r+3").unwrap();
+    /// let mut rng = StdRng::seed_from_u64(7);
+    ///
+    /// check.roll_with_advantage_and_rng(AdvState::Advantage, &mut rng);
+    /// ```
+    pub fn roll_with_advantage_and_rng<R: Rng + ?Sized>(
+        &self,
+        adv: AdvState,
+        rng: &mut R,
+    ) -> CheckOutcome {
+        let r1 = rng.gen_range(1, 21);
+        let r2 = rng.gen_range(1, 21);
+        let mods = self
+            .modifiers
+            .iter()
+            .map(|m| m.roll_with_rng(rng))
+            .collect();
+        CheckOutcome::new(adv, r1, r2, mods)
+            .with_min(self.min)
+            .with_crit(self.crit)
+            .with_target(self.target)
+    }
+}
+
+impl Check {
+    /// Calculate the exact probability distribution of the score of
+    /// this check. Unbound variables count as 0.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Check};
+    ///
+    /// let dist = Check::new("r+3").unwrap().distribution();
+    ///
+    /// assert_eq!(dist.min(), 4);
+    /// assert_eq!(dist.max(), 23);
+    /// ```
+    pub fn distribution(&self) -> Distribution {
+        self.d20_distribution(self.adv)
+            .convolve(&self.modifier_distribution())
+    }
+
+    /// Get the distribution of the value of the d20 that is kept,
+    /// after it is raised to the minimum value.
+    fn d20_distribution(&self, adv: AdvState) -> Distribution {
+        let d20 = Distribution::d20(adv);
+
+        match self.min {
+            Some(min) => Distribution::from_scores(d20.iter().map(|(face, p)| (face.max(min), p))),
+            None => d20,
+        }
+    }
+
+    /// Get the distribution of the total of the modifiers.
+    fn modifier_distribution(&self) -> Distribution {
+        modifier::sum(self.modifiers.iter().map(Modifier::distribution))
+    }
+
+    /// Calculate the chance of this check meeting or beating `dc`.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Check};
+    ///
+    /// let chance = Check::new("r+5").unwrap().chance(15);
+    ///
+    /// assert!((chance.success() - 0.55).abs() < 1e-9);
+    /// assert!((chance.critical() - 0.05).abs() < 1e-9);
+    /// assert!((chance.fail() - 0.05).abs() < 1e-9);
+    /// ```
+    pub fn chance(&self, dc: Score) -> CheckChance {
+        self.chance_with_advantage(dc, self.adv)
+    }
+
+    /// Calculate the chance of this check meeting or beating `dc`,
+    /// using `adv` to override the advantage state.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Check, AdvState};
+    ///
+    /// // Bless adds 1d4 to the check
+    /// let check = Check::new("r+5+1d4").unwrap();
+    ///
+    /// let normal = check.chance(15);
+    /// let advantage = check.chance_with_advantage(15, AdvState::Advantage);
+    ///
+    /// assert!(advantage.success() > normal.success());
+    /// ```
+    pub fn chance_with_advantage(&self, dc: Score, adv: AdvState) -> CheckChance {
+        // Crits and fails go by the natural roll, before any minimum
+        let d20 = Distribution::d20(adv);
+        let mods = self.modifier_distribution();
+
+        let crit = self.crit_range();
+
+        let mut chance = CheckChance {
+            success: 0.0,
+            success_with_crits: 0.0,
+            critical: d20.at_least(crit),
+            fail: d20.probability(1),
+        };
+
+        for (face, p) in d20.iter() {
+            let raised = self.min.map_or(face, |min| face.max(min));
+            let success = p * mods.at_least(dc - raised);
+            chance.success += success;
+            if face >= crit {
+                chance.success_with_crits += p;
+            } else if face != 1 {
+                chance.success_with_crits += success;
+            }
+        }
+
+        chance
+    }
+}
+
+/// The chance of a check succeeding against a DC.
+///
+/// This is normally constructed by calling `chance()` on a `Check`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CheckChance {
+    success: f64,
+    success_with_crits: f64,
+    critical: f64,
+    fail: f64,
+}
+
+impl CheckChance {
+    /// The chance that the score of the check meets or beats the DC.
+    ///
+    /// This only compares the score with the DC; a natural 20 or a
+    /// natural 1 is not treated specially.
+    pub fn success(&self) -> f64 {
+        self.success
+    }
+
+    /// The chance that the check succeeds when a critical success always
+    /// succeeds and a natural 1 always fails, as for attack rolls.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Check};
+    ///
+    /// let chance = Check::new("r+2").unwrap().chance(25);
+    ///
+    /// assert_eq!(chance.success(), 0.0);
+    /// assert!((chance.success_with_crits() - 0.05).abs() < 1e-9);
+    /// ```
+    pub fn success_with_crits(&self) -> f64 {
+        self.success_with_crits
+    }
+
+    /// The chance of rolling a critical success (a natural 20, or any
+    /// roll in the crit range of the check).
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Check};
+    ///
+    /// let chance = Check::new("r+5c19").unwrap().chance(15);
+    ///
+    /// assert!((chance.critical() - 0.1).abs() < 1e-9);
+    /// ```
+    pub fn critical(&self) -> f64 {
+        self.critical
+    }
+
+    /// The chance of rolling a critical failure (a natural 1).
+    pub fn fail(&self) -> f64 {
+        self.fail
     }
 }
 
 impl RollExpression for Check {
     type Outcome = CheckOutcome;
 
-    fn roll(&self) -> Self::Outcome {
-        self.roll_with_advantage(self.adv)
+    fn roll_with_rng<R: Rng + ?Sized>(&self, rng: &mut R) -> Self::Outcome {
+        self.roll_with_advantage_and_rng(self.adv, rng)
+    }
+}
+
+impl Check {
+    /// Whether this check is a plain d20 roll, not counting its target.
+    pub(crate) fn is_plain(&self) -> bool {
+        self.adv == AdvState::Neutral
+            && self.modifiers.is_empty()
+            && self.min.is_none()
+            && self.crit.is_none()
+    }
+
+    /// Write this check without its target, eg. `a+5c19`.
+    pub(crate) fn write_without_target(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let adv = match self.adv {
+            AdvState::Neutral => "r",
+            AdvState::Advantage => "a",
+            AdvState::Disadvantage => "d",
+        };
+        write!(f, "{}", adv)?;
+
+        match self.modifiers.first() {
+            Some(first) if !first.is_negative() => write!(f, "+")?,
+            _ => (),
+        }
+        modifier::write_sum(f, &self.modifiers)?;
+        if let Some(crit) = self.crit {
+            // The crit range would be read as part of a variable's name
+            if self.modifiers.last().and_then(Modifier::variable).is_some() {
+                write!(f, " ")?;
+            }
+            write!(f, "c{}", crit)?;
+        }
+        if let Some(min) = self.min {
+            write!(f, " min{}", min)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks are written in a canonical form of the notation they are
+/// parsed from, so parsing the written check gives the same `Check`
+/// back.
+///
+/// ```
+/// use critfail::{RollExpression, Check};
+///
+/// let check = Check::new("+5 c19 vs DC 15").unwrap();
+/// assert_eq!(check.to_string(), "r+5c19 vs 15");
+/// assert_eq!(Check::new(&check.to_string()).unwrap(), check);
+/// ```
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_without_target(f)?;
+        if let Some(target) = self.target {
+            write!(f, " vs {}", target)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn with_limits_round_trip() {
+        let check = Check::new("r+3").unwrap();
+        for check in &[
+            check.clone().with_crit_range(2),
+            check.clone().with_crit_range(20),
+            check.with_min(0),
+        ] {
+            assert_eq!(&Check::new(&check.to_string()).unwrap(), check);
+        }
+    }
+
+    #[test]
+    fn chance_with_min() {
+        let chance = Check::new("r+5c19 min19").unwrap().chance(30);
+
+        // Crits and fails still go by the natural roll
+        assert!((chance.critical() - 0.1).abs() < 1e-9);
+        assert!((chance.fail() - 0.05).abs() < 1e-9);
+        assert!((chance.success_with_crits() - 0.1).abs() < 1e-9);
+
+        // But every roll is raised to at least 19
+        let chance = Check::new("r+5 min19").unwrap().chance(24);
+        assert!((chance.success() - 1.0).abs() < 1e-9);
+        assert!((chance.success_with_crits() - 0.95).abs() < 1e-9);
+    }
+
+    #[test]
+    #[should_panic]
+    fn with_crit_range_too_high() {
+        Check::default().with_crit_range(25);
+    }
+
+    #[test]
+    #[should_panic]
+    fn with_crit_range_too_low() {
+        Check::default().with_crit_range(1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn with_min_negative() {
+        Check::default().with_min(-4);
+    }
+
+    #[test]
+    #[should_panic]
+    fn with_min_too_high() {
+        Check::default().with_min(21);
     }
 }
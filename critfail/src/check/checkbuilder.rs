@@ -0,0 +1,107 @@
+use crate::{AdvState, Check, Score, Sides};
+
+/// This is used to create a `Check` roll expression without parsing one
+/// from a string.
+///
+/// ```
+/// use critfail::{RollExpression, AdvState, Check, CheckBuilder};
+///
+/// let check = CheckBuilder::new()
+///     .advantage(AdvState::Advantage)
+///     .modifier(5)
+///     .dice(1, 4)
+///     .target(15)
+///     .build();
+///
+/// assert_eq!(check, Check::new("a+5+1d4 vs 15").unwrap());
+/// ```
+#[derive(Default)]
+pub struct CheckBuilder {
+    check: Check,
+}
+
+impl CheckBuilder {
+    /// Create a new `CheckBuilder`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set the advantage state of the check.
+    pub fn advantage(self, adv: AdvState) -> Self {
+        Self {
+            check: self.check.with_advantage(adv),
+        }
+    }
+
+    /// Add a constant modifier to the d20. This method can be chained
+    /// multiple times for multiple modifiers.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Check, CheckBuilder};
+    ///
+    /// let check = CheckBuilder::new().modifier(3).modifier(-1).build();
+    ///
+    /// assert_eq!(check, Check::new("r+3-1").unwrap());
+    /// ```
+    pub fn modifier(self, modifier: Score) -> Self {
+        Self {
+            check: self.check.with_modifier(modifier),
+        }
+    }
+
+    /// Add `num` dice with `sides` sides to the d20. The dice are
+    /// subtracted if `sides` is negative. This method can be chained
+    /// multiple times for multiple sets of dice.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Check, CheckBuilder};
+    ///
+    /// let check = CheckBuilder::new().modifier(2).dice(1, -4).build();
+    ///
+    /// assert_eq!(check, Check::new("r+2-1d4").unwrap());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num` or `sides` is 0.
+    pub fn dice(self, num: u32, sides: Sides) -> Self {
+        Self {
+            check: self.check.with_dice(num, sides),
+        }
+    }
+
+    /// Make the d20 count as `min` if it rolls lower.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` isn't from 0 to 20.
+    pub fn min(self, min: Score) -> Self {
+        Self {
+            check: self.check.with_min(min),
+        }
+    }
+
+    /// Make the check a critical success when the d20 rolls `crit` or
+    /// higher.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `crit` isn't from 2 to 20.
+    pub fn crit(self, crit: Score) -> Self {
+        Self {
+            check: self.check.with_crit_range(crit),
+        }
+    }
+
+    /// Set the DC (or AC) the check is rolled against.
+    pub fn target(self, target: Score) -> Self {
+        Self {
+            check: self.check.with_target(target),
+        }
+    }
+
+    /// Create a `Check` from this builder.
+    pub fn build(self) -> Check {
+        self.check
+    }
+}
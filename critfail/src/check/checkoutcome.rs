@@ -9,8 +9,10 @@ use std::fmt;
 
 /// The score of a roll that could be a critical hit/failure
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CritScore {
-    /// Critical success (rolled20 without modifiers).
+    /// Critical success (rolled a 20, or another roll in the crit range,
+    /// without modifiers).
     Critical,
     /// A normal roll.
     Normal(Score),
@@ -29,9 +31,13 @@ pub enum CritScore {
 /// let outcome: CheckOutcome = Check::new("r+1").unwrap().roll();
 /// ```
 #[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CheckOutcome {
     main: Score,
     other: Option<Score>,
+    min: Option<Score>,
+    crit: Option<Score>,
+    target: Option<Score>,
     modifiers: ModifiersOutcome,
 }
 
@@ -47,10 +53,48 @@ impl CheckOutcome {
         CheckOutcome {
             main,
             other,
+            min: None,
+            crit: None,
+            target: None,
             modifiers,
         }
     }
 
+    /// Raise the d20 of this outcome to `min` if it rolled lower.
+    pub(crate) fn with_min(self, min: Option<Score>) -> Self {
+        Self { min, ..self }
+    }
+
+    /// Make this outcome a critical success if the d20 is `crit` or
+    /// higher.
+    pub(crate) fn with_crit(self, crit: Option<Score>) -> Self {
+        Self { crit, ..self }
+    }
+
+    /// Set the DC (or AC) this outcome was rolled against.
+    pub(crate) fn with_target(self, target: Option<Score>) -> Self {
+        Self { target, ..self }
+    }
+
+    /// Get the value the kept d20 counts as, after it is raised to the
+    /// minimum value (eg. from `r+7 min10`).
+    ///
+    /// ```
+    /// use critfail::CheckOutcomeBuilder;
+    ///
+    /// // (3→10)+7
+    /// let outcome = CheckOutcomeBuilder::new().check(3).min(10).modifier(7).build();
+    ///
+    /// assert_eq!(outcome.kept_d20(), 3);
+    /// assert_eq!(outcome.d20(), 10);
+    /// ```
+    pub fn d20(&self) -> Score {
+        match self.min {
+            Some(min) => max(self.main, min),
+            None => self.main,
+        }
+    }
+
     /// Get the score of a `CheckOutcome`.
     ///
     /// This is the plain numerical score of a roll, without accounting
@@ -81,7 +125,7 @@ impl CheckOutcome {
     /// assert_eq!(fail.score(), 5);
     /// ```
     pub fn score(&self) -> Score {
-        self.main + self.modifiers.score()
+        self.d20() + self.modifiers.score()
     }
 
     /// Get the score of a `CheckOutcome` that could be a critical success/failure.
@@ -111,31 +155,139 @@ impl CheckOutcome {
     /// assert_eq!(fail.crit_score(), CritScore::Fail);
     /// ```
     pub fn crit_score(&self) -> CritScore {
-        match self.main {
-            1 => CritScore::Fail,
-            20 => CritScore::Critical,
-            _ => CritScore::Normal(self.score()),
+        // Crits and fails go by the natural roll, before any minimum
+        let d20 = self.kept_d20();
+
+        if d20 >= self.crit.unwrap_or(20) {
+            CritScore::Critical
+        } else if d20 == 1 {
+            CritScore::Fail
+        } else {
+            CritScore::Normal(self.score())
         }
     }
 }
 
+impl CheckOutcome {
+    /// Get the d20 that was kept, as it was rolled. With advantage this
+    /// is the higher of the two d20s, and with disadvantage it is the
+    /// lower.
+    ///
+    /// ```
+    /// use critfail::CheckOutcomeBuilder;
+    ///
+    /// // (15/8)+2
+    /// let outcome = CheckOutcomeBuilder::new().check_adv(8, 15).modifier(2).build();
+    ///
+    /// assert_eq!(outcome.kept_d20(), 15);
+    /// assert_eq!(outcome.discarded_d20(), Some(8));
+    /// ```
+    pub fn kept_d20(&self) -> Score {
+        self.main
+    }
+
+    /// Get the d20 that was discarded because the check had advantage
+    /// or disadvantage, or `None` if only one d20 was rolled.
+    pub fn discarded_d20(&self) -> Option<Score> {
+        self.other
+    }
+
+    /// Get the parts of the modifier that were added to the d20, in the
+    /// order they were written.
+    ///
+    /// ```
+    /// use critfail::{CheckOutcomeBuilder, OutcomePart};
+    ///
+    /// // (10)-[1+2]+3
+    /// let outcome = CheckOutcomeBuilder::new()
+    ///     .check(10)
+    ///     .dice(-4, vec![1, 2])
+    ///     .modifier(3)
+    ///     .build();
+    ///
+    /// let parts: Vec<&OutcomePart> = outcome.modifiers().collect();
+    /// assert_eq!(parts.len(), 2);
+    /// assert_eq!(parts[0].sides(), Some(-4));
+    /// assert_eq!(parts[0].values(), vec![1, 2]);
+    /// assert_eq!(parts[1].values(), vec![3]);
+    /// ```
+    pub fn modifiers(&self) -> std::slice::Iter<'_, OutcomePart> {
+        self.modifiers.iter()
+    }
+
+    /// Get the DC (or AC) this check was rolled against, if it had one.
+    pub fn target(&self) -> Option<Score> {
+        self.target
+    }
+
+    /// Get whether this check met or beat its target, or `None` if it
+    /// wasn't rolled against a target.
+    ///
+    /// A critical success always succeeds and a critical failure
+    /// always fails.
+    ///
+    /// ```
+    /// use critfail::CheckOutcomeBuilder;
+    ///
+    /// // (12)+4 vs 15
+    /// let success = CheckOutcomeBuilder::new()
+    ///     .check(12)
+    ///     .modifier(4)
+    ///     .target(15)
+    ///     .build();
+    ///
+    /// // (20)-1 vs 25
+    /// let critical = CheckOutcomeBuilder::new()
+    ///     .check(20)
+    ///     .modifier(-1)
+    ///     .target(25)
+    ///     .build();
+    ///
+    /// // (10)+4
+    /// let untargeted = CheckOutcomeBuilder::new()
+    ///     .check(10)
+    ///     .modifier(4)
+    ///     .build();
+    ///
+    /// assert_eq!(success.success(), Some(true));
+    /// assert_eq!(critical.success(), Some(true));
+    /// assert_eq!(untargeted.success(), None);
+    /// assert_eq!(format!("{}", success), "Success (16)");
+    /// assert_eq!(format!("{:?}", success), "(12)+4 vs 15");
+    /// ```
+    pub fn success(&self) -> Option<bool> {
+        let target = self.target?;
+
+        Some(match self.crit_score() {
+            CritScore::Critical => true,
+            CritScore::Normal(score) => score >= target,
+            CritScore::Fail => false,
+        })
+    }
+}
+
 impl fmt::Display for CheckOutcome {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self.crit_score() {
-            CritScore::Critical => write!(f, "Critical"),
-            CritScore::Normal(score) => write!(f, "{}", score),
-            CritScore::Fail => write!(f, "Fail"),
+        match (self.crit_score(), self.success()) {
+            (CritScore::Critical, _) => write!(f, "Critical"),
+            (CritScore::Normal(score), None) => write!(f, "{}", score),
+            (CritScore::Normal(score), Some(true)) => write!(f, "Success ({})", score),
+            (CritScore::Normal(score), Some(false)) => write!(f, "Failure ({})", score),
+            (CritScore::Fail, _) => write!(f, "Fail"),
         }
     }
 }
 
 impl fmt::Debug for CheckOutcome {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}", self.main)?;
+        if self.d20() != self.main {
+            write!(f, "→{}", self.d20())?;
+        }
         if let Some(other) = self.other {
-            write!(f, "({}/{})", self.main, other)?
-        } else {
-            write!(f, "({})", self.main)?
+            write!(f, "/{}", other)?;
         }
+        write!(f, ")")?;
 
         let mods = format!("{:?}", self.modifiers);
 
@@ -146,6 +298,10 @@ impl fmt::Debug for CheckOutcome {
             write!(f, "{}", mods)?
         }
 
+        if let Some(target) = self.target {
+            write!(f, " vs {}", target)?
+        }
+
         Ok(())
     }
 }
@@ -173,6 +329,9 @@ pub struct CheckOutcomeBuilder {
     adv: AdvState,
     r1: Score,
     r2: Score,
+    min: Option<Score>,
+    crit: Option<Score>,
+    target: Option<Score>,
     modifiers: Vec<OutcomePart>,
 }
 
@@ -255,6 +414,74 @@ impl CheckOutcomeBuilder {
         }
     }
 
+    /// Set a minimum value for the d20, like for Reliable Talent.
+    ///
+    /// ```
+    /// use critfail::CheckOutcomeBuilder;
+    ///
+    /// // To create a result that could come from rolling 'r+7 min10'
+    /// let outcome = CheckOutcomeBuilder::new()
+    ///     .check(3)
+    ///     .min(10)
+    ///     .modifier(7)
+    ///     .build();
+    ///
+    /// assert_eq!(outcome.score(), 17);
+    /// assert_eq!(
+    ///     format!("{:?}", outcome),
+    ///     "(3→10)+7"
+    /// );
+    /// ```
+    pub fn min(self, min: Score) -> Self {
+        Self {
+            min: Some(min),
+            ..self
+        }
+    }
+
+    /// Set the lowest d20 roll that counts as a critical success.
+    ///
+    /// ```
+    /// use critfail::{CheckOutcomeBuilder, CritScore};
+    ///
+    /// // To create a result that could come from rolling 'r+5c19'
+    /// let outcome = CheckOutcomeBuilder::new()
+    ///     .check(19)
+    ///     .crit(19)
+    ///     .modifier(5)
+    ///     .build();
+    ///
+    /// assert_eq!(outcome.crit_score(), CritScore::Critical);
+    /// ```
+    pub fn crit(self, crit: Score) -> Self {
+        Self {
+            crit: Some(crit),
+            ..self
+        }
+    }
+
+    /// Set the DC (or AC) the check was rolled against.
+    ///
+    /// ```
+    /// use critfail::CheckOutcomeBuilder;
+    ///
+    /// // To create a result that could come from rolling 'r+2 vs 15'
+    /// let outcome = CheckOutcomeBuilder::new()
+    ///     .check(9)
+    ///     .modifier(2)
+    ///     .target(15)
+    ///     .build();
+    ///
+    /// assert_eq!(outcome.success(), Some(false));
+    /// assert_eq!(format!("{}", outcome), "Failure (11)");
+    /// ```
+    pub fn target(self, target: Score) -> Self {
+        Self {
+            target: Some(target),
+            ..self
+        }
+    }
+
     /// Add a constant modifier to the roll. This method can be chained
     /// multiple times for multiple modifiers.
     ///
@@ -311,6 +538,9 @@ impl CheckOutcomeBuilder {
     /// Create a `CheckOutcome` from this builder.
     pub fn build(self) -> CheckOutcome {
         CheckOutcome::new(self.adv, self.r1, self.r2, self.modifiers)
+            .with_min(self.min)
+            .with_crit(self.crit)
+            .with_target(self.target)
     }
 }
 
@@ -368,6 +598,85 @@ mod tests {
         assert_eq!(format!("{:?}", r), "(20/4)-[2+3]+3");
     }
 
+    #[test]
+    fn accessors() {
+        let r =
+            CheckOutcome::new(Disadvantage, 4, 12, vec![D(-4, vec![2]), M(3)]).with_min(Some(10));
+        assert_eq!(r.kept_d20(), 4);
+        assert_eq!(r.discarded_d20(), Some(12));
+        assert_eq!(r.d20(), 10);
+        assert_eq!(
+            r.modifiers().cloned().collect::<Vec<_>>(),
+            vec![D(-4, vec![2]), M(3)]
+        );
+
+        let r = CheckOutcome::new(Neutral, 17, 3, vec![]);
+        assert_eq!(r.kept_d20(), 17);
+        assert_eq!(r.discarded_d20(), None);
+        assert_eq!(r.modifiers().count(), 0);
+    }
+
+    #[test]
+    fn min() {
+        let r = CheckOutcome::new(Neutral, 3, 15, vec![M(7)]).with_min(Some(10));
+        assert_eq!(r.score(), 17);
+        assert_eq!(format!("{}", r), "17");
+        assert_eq!(format!("{:?}", r), "(3→10)+7");
+
+        let r = CheckOutcome::new(Advantage, 4, 12, vec![M(7)]).with_min(Some(10));
+        assert_eq!(r.score(), 19);
+        assert_eq!(format!("{:?}", r), "(12/4)+7");
+
+        let r = CheckOutcome::new(Disadvantage, 4, 12, vec![]).with_min(Some(10));
+        assert_eq!(r.score(), 10);
+        assert_eq!(format!("{:?}", r), "(4→10/12)");
+    }
+
+    #[test]
+    fn raised_rolls_keep_natural_crits() {
+        // A natural 1 is still a fail after it's raised
+        let r = CheckOutcome::new(Neutral, 1, 15, vec![M(7)]).with_min(Some(10));
+        assert_eq!(r.score(), 17);
+        assert_eq!(r.crit_score(), CritScore::Fail);
+
+        // And raising a roll into the crit range doesn't make it a crit
+        let r = CheckOutcome::new(Neutral, 5, 15, vec![M(5)])
+            .with_min(Some(20))
+            .with_crit(Some(19));
+        assert_eq!(r.crit_score(), CritScore::Normal(25));
+    }
+
+    #[test]
+    fn crit_range() {
+        let r = CheckOutcome::new(Neutral, 19, 4, vec![M(5)]).with_crit(Some(19));
+        assert_eq!(r.score(), 24);
+        assert_eq!(format!("{}", r), "Critical");
+        assert_eq!(format!("{:?}", r), "(19)+5");
+
+        let r = CheckOutcome::new(Neutral, 17, 4, vec![M(5)]).with_crit(Some(18));
+        assert_eq!(r.crit_score(), CritScore::Normal(22));
+
+        let r = CheckOutcome::new(Neutral, 19, 4, vec![M(5)]);
+        assert_eq!(r.crit_score(), CritScore::Normal(24));
+    }
+
+    #[test]
+    fn target() {
+        let r = CheckOutcome::new(Neutral, 10, 4, vec![M(5)]).with_target(Some(15));
+        assert_eq!(r.success(), Some(true));
+        assert_eq!(format!("{}", r), "Success (15)");
+        assert_eq!(format!("{:?}", r), "(10)+5 vs 15");
+
+        let r = CheckOutcome::new(Advantage, 9, 4, vec![M(5)]).with_target(Some(15));
+        assert_eq!(r.success(), Some(false));
+        assert_eq!(format!("{}", r), "Failure (14)");
+        assert_eq!(format!("{:?}", r), "(9/4)+5 vs 15");
+
+        let r = CheckOutcome::new(Neutral, 1, 4, vec![M(20)]).with_target(Some(15));
+        assert_eq!(r.success(), Some(false));
+        assert_eq!(format!("{}", r), "Fail");
+    }
+
     #[test]
     fn fail() {
         let r = CheckOutcome::new(Disadvantage, 1, 4, vec![D(-4, vec![2, 3]), M(3)]);
@@ -1,6 +1,7 @@
 use std::str::FromStr;
 
-use crate::AdvState::*;
+use crate::modifier::modifierparse;
+use crate::parse::{self, CheckExpr};
 use crate::Check;
 use crate::ParseError;
 
@@ -8,43 +9,47 @@ impl FromStr for Check {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // Figure out the advantage from the first character
-        let (adv, i) = if s.starts_with('r') {
-            (Neutral, 1)
-        } else if s.starts_with('a') {
-            (Advantage, 1)
-        } else if s.starts_with('d') {
-            (Disadvantage, 1)
-        } else if s.starts_with('+') || s.starts_with('-') {
-            (Neutral, 0)
-        } else {
-            return Err(ParseError::new(s));
-        };
-
-        // If the character after the advantage character is a +, skip it
-        let i = if s[i..].starts_with('+') { i + 1 } else { i };
-
-        // The rest is the modifier
-        let modifier = s[i..].parse()?;
-
-        Ok(Check { adv, modifier })
+        Ok(parse::check(s)?.into())
+    }
+}
+
+impl From<CheckExpr> for Check {
+    fn from(expr: CheckExpr) -> Self {
+        Check {
+            adv: expr.adv,
+            // The parser doesn't allow damage types in a check
+            modifiers: modifierparse::flatten(expr.modifier)
+                .into_iter()
+                .map(|(modifier, _)| modifier)
+                .collect(),
+            min: expr.min,
+            crit: expr.crit,
+            target: expr.target,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
-    use crate::damage::DamagePart::Dice as D;
-    use crate::damage::DamagePart::Modifier as M;
+    use crate::AdvState::*;
     use crate::*;
 
+    fn dice(num: u32, sides: Sides) -> Modifier {
+        Modifier::from_dice(num, sides)
+    }
+
+    fn constant(value: Score) -> Modifier {
+        Modifier::from_constant(value)
+    }
+
     #[test]
     fn basic() {
         assert_eq!(
             "r".parse::<Check>().unwrap(),
             Check {
                 adv: Neutral,
-                modifier: Damage(vec![])
+                modifiers: vec![],
+                ..Default::default()
             }
         );
 
@@ -52,7 +57,8 @@ mod tests {
             "a".parse::<Check>().unwrap(),
             Check {
                 adv: Advantage,
-                modifier: Damage(vec![])
+                modifiers: vec![],
+                ..Default::default()
             }
         );
 
@@ -60,18 +66,27 @@ mod tests {
             "d".parse::<Check>().unwrap(),
             Check {
                 adv: Disadvantage,
-                modifier: Damage(vec![])
+                modifiers: vec![],
+                ..Default::default()
             }
         );
     }
 
+    #[test]
+    fn dangling_plus() {
+        assert_eq!("r+".parse::<Check>().unwrap(), Check::new("r").unwrap());
+        assert_eq!("+".parse::<Check>().unwrap(), Check::new("r").unwrap());
+        assert_eq!("a+".parse::<Check>().unwrap(), Check::new("a").unwrap());
+    }
+
     #[test]
     fn with_modifiers() {
         assert_eq!(
             "r+3".parse::<Check>().unwrap(),
             Check {
                 adv: Neutral,
-                modifier: Damage(vec![M(3)])
+                modifiers: vec![constant(3)],
+                ..Default::default()
             }
         );
 
@@ -79,7 +94,8 @@ mod tests {
             "d+5".parse::<Check>().unwrap(),
             Check {
                 adv: Disadvantage,
-                modifier: Damage(vec![M(5)])
+                modifiers: vec![constant(5)],
+                ..Default::default()
             }
         );
 
@@ -87,7 +103,8 @@ mod tests {
             "a-2".parse::<Check>().unwrap(),
             Check {
                 adv: Advantage,
-                modifier: Damage(vec![M(-2)])
+                modifiers: vec![constant(-2)],
+                ..Default::default()
             }
         );
 
@@ -95,7 +112,8 @@ mod tests {
             "r+1d4+2".parse::<Check>().unwrap(),
             Check {
                 adv: Neutral,
-                modifier: Damage(vec![D(1, 4), M(2)])
+                modifiers: vec![dice(1, 4), constant(2)],
+                ..Default::default()
             }
         );
     }
@@ -106,7 +124,8 @@ mod tests {
             "+3".parse::<Check>().unwrap(),
             Check {
                 adv: Neutral,
-                modifier: Damage(vec![M(3)])
+                modifiers: vec![constant(3)],
+                ..Default::default()
             }
         );
 
@@ -114,7 +133,8 @@ mod tests {
             "-2".parse::<Check>().unwrap(),
             Check {
                 adv: Neutral,
-                modifier: Damage(vec![M(-2)])
+                modifiers: vec![constant(-2)],
+                ..Default::default()
             }
         );
 
@@ -122,7 +142,8 @@ mod tests {
             "-1d4+2".parse::<Check>().unwrap(),
             Check {
                 adv: Neutral,
-                modifier: Damage(vec![D(1, -4), M(2)])
+                modifiers: vec![dice(1, -4), constant(2)],
+                ..Default::default()
             }
         );
 
@@ -130,19 +151,128 @@ mod tests {
             "+1d4".parse::<Check>().unwrap(),
             Check {
                 adv: Neutral,
-                modifier: Damage(vec![D(1, 4)])
+                modifiers: vec![dice(1, 4)],
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn min() {
+        assert_eq!(
+            "r+7 min10".parse::<Check>().unwrap(),
+            Check {
+                adv: Neutral,
+                modifiers: vec![constant(7)],
+                min: Some(10),
+                ..Default::default()
+            }
+        );
+
+        assert_eq!(
+            "a min10".parse::<Check>().unwrap(),
+            Check {
+                adv: Advantage,
+                modifiers: vec![],
+                min: Some(10),
+                ..Default::default()
+            }
+        );
+
+        // Without a space, the minimum belongs to the dice
+        assert_eq!(
+            "r+1d4min2".parse::<Check>().unwrap(),
+            Check {
+                adv: Neutral,
+                modifiers: vec!["1d4min2".parse().unwrap()],
+                min: None,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn crit_range() {
+        assert_eq!(
+            "r+5c19".parse::<Check>().unwrap(),
+            Check {
+                adv: Neutral,
+                modifiers: vec![constant(5)],
+                crit: Some(19),
+                ..Default::default()
+            }
+        );
+
+        assert_eq!(
+            "ac18".parse::<Check>().unwrap(),
+            Check {
+                adv: Advantage,
+                modifiers: vec![],
+                crit: Some(18),
+                ..Default::default()
+            }
+        );
+
+        assert_eq!(
+            "r+7c19 min10".parse::<Check>().unwrap(),
+            Check {
+                adv: Neutral,
+                modifiers: vec![constant(7)],
+                min: Some(10),
+                crit: Some(19),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn target() {
+        for s in &["r+5 vs 15", "r+5 vs DC 15", "r+5  vs  dc15"] {
+            assert_eq!(
+                s.parse::<Check>().unwrap(),
+                Check {
+                    adv: Neutral,
+                    modifiers: vec![constant(5)],
+                    target: Some(15),
+                    ..Default::default()
+                }
+            );
+        }
+
+        assert_eq!(
+            "a+7c19 min10 vs 20".parse::<Check>().unwrap(),
+            Check {
+                adv: Advantage,
+                modifiers: vec![constant(7)],
+                min: Some(10),
+                crit: Some(19),
+                target: Some(20),
             }
         );
     }
 
     #[test]
     fn invalid() {
+        assert!("r+5 vs".parse::<Check>().is_err());
+        assert!("r+5vs15".parse::<Check>().is_err());
+        assert!("r+5 vs XY 15".parse::<Check>().is_err());
+        assert!("r+5c".parse::<Check>().is_err());
+        assert!("r+5c1".parse::<Check>().is_err());
+        assert!("r+5c21".parse::<Check>().is_err());
+        assert!("r+5c19c18".parse::<Check>().is_err());
+        assert!("r+7 min".parse::<Check>().is_err());
+        assert!("r+1d4 fire".parse::<Check>().is_err());
+        assert!("r+7min10".parse::<Check>().is_err());
+        assert!("r min21".parse::<Check>().is_err());
         assert!("r+r+3".parse::<Check>().is_err());
         assert!("1+r+3".parse::<Check>().is_err());
         assert!("2d8".parse::<Check>().is_err());
         assert!("5".parse::<Check>().is_err());
         assert!("+r+2d8".parse::<Check>().is_err());
         assert!("r+3+bad".parse::<Check>().is_err());
-        assert!("r+3+1d4/2".parse::<Check>().is_err());
+        assert!("r+3+1d4/0".parse::<Check>().is_err());
+        assert!("r+3 crit max".parse::<Check>().is_err());
+        assert!("r+ vs 10".parse::<Check>().is_err());
+        assert!("r+c19".parse::<Check>().is_err());
     }
 }
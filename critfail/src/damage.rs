@@ -1,20 +1,34 @@
 use rand::Rng;
+use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+use std::fmt;
 
+use crate::modifier::{self, Modifier};
+use crate::Distribution;
 use crate::OutcomePart;
 use crate::RollExpression;
 use crate::{CheckOutcome, CritScore};
-use crate::{Score, Sides};
+use crate::{Score, Sides, UnboundVariable};
 
+mod damagebuilder;
 mod damageoutcome;
 mod damageparse;
+mod damagetype;
+mod defenses;
 
+pub use damagebuilder::DamageBuilder;
 pub use damageoutcome::{DamageOutcome, DamageOutcomeBuilder};
+pub use damagetype::DamageType;
+pub use defenses::{AdjustedDamage, Defense, Defenses};
 
-// TODO: DamagePart should not be `pub` (once Check stops using Damage)
+/// One part of a `Damage`: a modifier, and the type of damage it deals
+/// if it has one.
 #[derive(PartialEq, Debug, Clone)]
-pub enum DamagePart {
-    Dice(u32, Sides),
-    Modifier(Score),
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct DamagePart {
+    pub(crate) modifier: Modifier,
+    pub(crate) damage_type: Option<DamageType>,
 }
 
 /// A list of dice to roll and modifiers to add, usually used for
@@ -30,14 +44,219 @@ pub enum DamagePart {
 /// print!("{}", outcome);   // Prints something like "11"
 /// print!("{:?}", outcome); // Prints something like "[2+5]+4"
 /// ```
-#[derive(Debug, PartialEq, Clone)]
-pub struct Damage(pub Vec<DamagePart>);
+///
+/// Parts of the damage can be given a damage type. The type applies to
+/// all the parts before it that don't have a type yet.
+///
+/// ```
+/// use critfail::{RollExpression, Damage};
+///
+/// let damage = Damage::new("1d8+3 slashing + 2d6 fire").unwrap();
+///
+/// let outcome = damage.roll();
+///
+/// print!("{:?}", outcome); // Prints something like "[5]+3 slashing+[2+6] fire"
+/// ```
+///
+/// Modifiers can be variables like `$str`, which are given values with
+/// `bind()`. Variables that haven't been given a value count as 0.
+///
+/// ```
+/// use critfail::{RollExpression, Damage};
+/// use std::collections::HashMap;
+///
+/// let damage = Damage::new("1d8+$str").unwrap();
+/// assert_eq!(damage.variables(), vec!["str"]);
+///
+/// let context: HashMap<String, i32> = vec![("str".to_string(), 4)].into_iter().collect();
+/// assert_eq!(damage.bind(&context).unwrap(), Damage::new("1d8+4").unwrap());
+/// ```
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "DamageFields"))]
+pub struct Damage {
+    pub(crate) parts: Vec<DamagePart>,
+    /// How this damage is rolled on a critical hit.
+    pub(crate) crit_rule: CritRule,
+}
+
+/// The fields of a deserialized `Damage`, before its crit rule is
+/// checked.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct DamageFields {
+    parts: Vec<DamagePart>,
+    crit_rule: CritRule,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<DamageFields> for Damage {
+    type Error = &'static str;
+
+    fn try_from(fields: DamageFields) -> Result<Self, Self::Error> {
+        // Like the parser, only allow each set of dice in the damage once
+        if let CritRule::OnlyParts(sets) = &fields.crit_rule {
+            let count: usize = fields.parts.iter().map(|p| p.modifier.dice_sets()).sum();
+            for (i, set) in sets.iter().enumerate() {
+                if *set >= count || sets[..i].contains(set) {
+                    return Err("invalid set of dice in crit rule");
+                }
+            }
+        }
+
+        Ok(Damage {
+            parts: fields.parts,
+            crit_rule: fields.crit_rule,
+        })
+    }
+}
+
+/// How the damage of a critical hit is rolled.
+///
+/// ```
+/// use critfail::{RollExpression, Damage, CritRule};
+///
+/// let damage = Damage::new("1d8+3").unwrap().with_crit_rule(CritRule::MaxPlusRoll);
+///
+/// // Max + roll: the d8 counts as an 8, and is rolled once more
+/// let dist = damage.crit_distribution();
+/// assert_eq!(dist.min(), 12);
+/// assert_eq!(dist.max(), 19);
+/// ```
+#[derive(PartialEq, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CritRule {
+    /// Roll all the positive dice twice (the standard rule).
+    #[default]
+    DoubleDice,
+    /// Count all the positive dice as rolling their highest value, and
+    /// then roll them once more.
+    MaxPlusRoll,
+    /// Roll the damage normally, and then double the total (including
+    /// modifiers).
+    DoubleTotal,
+    /// Only roll the positive dice twice for these sets of dice. Sets of
+    /// dice are counted from 0 and modifiers are not counted, so the
+    /// `2d6` in `1d8+3+2d6` is set 1.
+    OnlyParts(Vec<usize>),
+}
 
 impl Damage {
+    /// Get the names of the variables in this `Damage` (without the
+    /// `$`), in the order they first appear.
+    pub fn variables(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        for part in &self.parts {
+            part.modifier.add_variables(&mut names);
+        }
+        names
+    }
+
+    /// Replace each variable in this `Damage` with its value in
+    /// `context`.
+    pub fn bind(&self, context: &HashMap<String, Score>) -> Result<Self, UnboundVariable> {
+        self.parts
+            .iter()
+            .map(|part| {
+                Ok(DamagePart {
+                    modifier: part.modifier.bind(context)?,
+                    damage_type: part.damage_type,
+                })
+            })
+            .collect::<Result<_, _>>()
+            .map(|parts| Damage {
+                parts,
+                crit_rule: self.crit_rule.clone(),
+            })
+    }
+
+    /// Add `num` dice with `sides` sides to this `Damage`. The dice are
+    /// subtracted if `sides` is negative.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Damage};
+    ///
+    /// let damage = Damage::new("1d8+3").unwrap().with_dice(2, 6).with_dice(1, -4);
+    ///
+    /// assert_eq!(damage, Damage::new("1d8+3+2d6-1d4").unwrap());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num` or `sides` is 0.
+    pub fn with_dice(mut self, num: u32, sides: Sides) -> Self {
+        self.parts.push(Modifier::from_dice(num, sides).into());
+        self
+    }
+
+    /// Add a constant `modifier` to this `Damage`.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Damage};
+    ///
+    /// let damage = Damage::new("2d6").unwrap().with_modifier(4).with_modifier(-1);
+    ///
+    /// assert_eq!(damage, Damage::new("2d6+4-1").unwrap());
+    /// ```
+    pub fn with_modifier(mut self, modifier: Score) -> Self {
+        self.parts.push(Modifier::from_constant(modifier).into());
+        self
+    }
+
+    /// Give a damage type to the parts of this `Damage` that don't have
+    /// a type yet, in the same way as writing the type after them.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Damage, DamageType};
+    ///
+    /// let damage = Damage::new("1d8+3")
+    ///     .unwrap()
+    ///     .with_damage_type(DamageType::Slashing)
+    ///     .with_dice(2, 6)
+    ///     .with_damage_type(DamageType::Fire);
+    ///
+    /// assert_eq!(damage, Damage::new("1d8+3 slashing+2d6 fire").unwrap());
+    /// ```
+    pub fn with_damage_type(mut self, damage_type: DamageType) -> Self {
+        for part in self.parts.iter_mut().rev() {
+            if part.damage_type.is_some() {
+                break;
+            }
+            part.damage_type = Some(damage_type);
+        }
+        self
+    }
+
+    /// Get the rule used to roll this `Damage` on a critical hit.
+    pub fn crit_rule(&self) -> &CritRule {
+        &self.crit_rule
+    }
+
+    /// Use `rule` to roll this `Damage` on a critical hit.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Damage, CritRule};
+    ///
+    /// let damage = Damage::new("2d8+3").unwrap().with_crit_rule(CritRule::MaxPlusRoll);
+    /// let outcome = damage.crit_roll();
+    ///
+    /// print!("{:?}", outcome); // Prints something like "16+[3+7]+3"
+    /// assert!(outcome.score() >= 21);
+    /// ```
+    pub fn with_crit_rule(self, rule: CritRule) -> Self {
+        Self {
+            crit_rule: rule,
+            ..self
+        }
+    }
+
     /// Roll this `Damage` as though it were a critical hit.
     ///
-    /// This rolls all the positive dice in the `Damage` twice.
-    /// Modifiers and dice with negative values are only counted once.
+    /// By default, this rolls all the positive dice in the `Damage`
+    /// twice. Dice with extra rules (like `4d6kh3` or `2d6r<2`) apply
+    /// the rules to each set of dice separately. Modifiers and dice with
+    /// negative values are only counted once. A different `CritRule`
+    /// can be chosen with `with_crit_rule()`.
     ///
     /// ```
     /// use critfail::{RollExpression, Damage};
@@ -48,33 +267,171 @@ impl Damage {
     /// print!("{:?}", damage)  // Prints something like "[5+6+4+7]+3-[2]"
     /// ```
     pub fn crit_roll(&self) -> DamageOutcome {
-        let mut result = Vec::new();
+        self.crit_roll_with_rng(&mut rand::thread_rng())
+    }
 
-        for part in &self.0 {
-            match part {
-                DamagePart::Dice(_, sides) if *sides > 0 => {
-                    result.push(part.roll());
-                    result.push(part.roll());
-                }
-                _ => {
-                    result.push(part.roll());
-                }
-            }
+    /// Roll this `Damage` as though it were a critical hit, using `rng`
+    /// as the source of randomness.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Damage};
+    /// use rand::{rngs::StdRng, SeedableRng};
+    ///
+    /// // Great Weapon Fighting rerolls the doubled dice too
+    /// let damage = Damage::new("2d6r<2+3").unwrap();
+    /// let outcome = damage.crit_roll_with_rng(&mut StdRng::seed_from_u64(1));
+    ///
+    /// assert_eq!(format!("{:?}", outcome).matches('[').count(), 2);
+    /// ```
+    pub fn crit_roll_with_rng<R: Rng + ?Sized>(&self, rng: &mut R) -> DamageOutcome {
+        let rule = &self.crit_rule;
+        if *rule == CritRule::DoubleTotal {
+            return self.roll_with_rng(rng).doubled();
+        }
+
+        let mut dice = 0;
+        let mut result = Vec::new();
+        for part in &self.parts {
+            let parts = part.modifier.crit_roll(rule, &mut dice, rng);
+            result.extend(parts.into_iter().map(|p| part.with_type(p)));
         }
 
         DamageOutcome::new(result)
     }
 
-    /// Roll for damage, doubling if the check was a critical success.
+    /// Calculate the exact probability distribution of the score of
+    /// this `Damage`. Unbound variables count as 0.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Damage};
+    ///
+    /// let dist = Damage::new("3d12-1d4+6-2").unwrap().distribution();
+    ///
+    /// assert_eq!(dist.min(), 3);
+    /// assert_eq!(dist.max(), 39);
+    /// assert!((dist.mean() - 21.0).abs() < 1e-9);
+    /// assert!((dist.variance() - 37.0).abs() < 1e-9);
+    /// ```
+    pub fn distribution(&self) -> Distribution {
+        modifier::sum(self.parts.iter().map(|part| part.modifier.distribution()))
+    }
+
+    /// Calculate the exact probability distribution of the score of
+    /// this `Damage` when it is rolled as a critical hit.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Damage};
+    ///
+    /// let dist = Damage::new("1d8+3").unwrap().crit_distribution();
+    ///
+    /// assert_eq!(dist.min(), 5);
+    /// assert_eq!(dist.max(), 19);
+    /// ```
+    ///
+    /// The distribution follows the crit rule of the `Damage`.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Damage, CritRule};
+    ///
+    /// let damage = Damage::new("1d8+3").unwrap().with_crit_rule(CritRule::DoubleTotal);
+    /// let dist = damage.crit_distribution();
+    ///
+    /// assert_eq!(dist.min(), 8);
+    /// assert_eq!(dist.max(), 22);
+    /// assert_eq!(dist.probability(9), 0.0);
+    /// ```
+    pub fn crit_distribution(&self) -> Distribution {
+        let rule = &self.crit_rule;
+        if *rule == CritRule::DoubleTotal {
+            return self.distribution().scale(2);
+        }
+
+        let mut dice = 0;
+        modifier::sum(
+            self.parts
+                .iter()
+                .map(|part| part.modifier.crit_distribution(rule, &mut dice)),
+        )
+    }
+
+    /// Roll for damage, rolling critical damage if the check was a
+    /// critical success.
     pub fn roll_with_check(&self, check: &CheckOutcome) -> DamageOutcome {
         self.roll_with_score(check.crit_score())
     }
 
-    /// Roll for damage, doubling if the check was a critical success.
+    /// Roll for damage, rolling critical damage if the check was a
+    /// critical success, using `rng` as the source of randomness.
+    pub fn roll_with_check_and_rng<R: Rng + ?Sized>(
+        &self,
+        check: &CheckOutcome,
+        rng: &mut R,
+    ) -> DamageOutcome {
+        self.roll_with_score_and_rng(check.crit_score(), rng)
+    }
+
+    /// Roll for damage, rolling critical damage if the check was a
+    /// critical success.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Damage, CritRule, CritScore};
+    ///
+    /// let damage = Damage::new("1d6+3").unwrap().with_crit_rule(CritRule::MaxPlusRoll);
+    /// let outcome = damage.roll_with_score(CritScore::Critical);
+    ///
+    /// assert!(outcome.score() >= 10);
+    /// ```
     pub fn roll_with_score(&self, score: CritScore) -> DamageOutcome {
+        self.roll_with_score_and_rng(score, &mut rand::thread_rng())
+    }
+
+    /// Roll for damage, rolling critical damage if the check was a
+    /// critical success, using `rng` as the source of randomness.
+    pub fn roll_with_score_and_rng<R: Rng + ?Sized>(
+        &self,
+        score: CritScore,
+        rng: &mut R,
+    ) -> DamageOutcome {
         match score {
-            CritScore::Critical => self.crit_roll(),
-            _ => self.roll(),
+            CritScore::Critical => self.crit_roll_with_rng(rng),
+            _ => self.roll_with_rng(rng),
+        }
+    }
+}
+
+impl DamagePart {
+    /// Give `outcome` the damage type of this part, if it has one.
+    fn with_type(&self, outcome: OutcomePart) -> OutcomePart {
+        match self.damage_type {
+            Some(t) => OutcomePart::Typed(Box::new(outcome), t),
+            None => outcome,
+        }
+    }
+}
+
+impl From<Modifier> for DamagePart {
+    fn from(modifier: Modifier) -> Self {
+        Self {
+            modifier,
+            damage_type: None,
+        }
+    }
+}
+
+/// Damage can be made from a list of modifiers without damage types.
+///
+/// ```
+/// use critfail::{RollExpression, Damage, Modifier};
+///
+/// let damage = Damage::from(vec![Modifier::from_dice(2, 6), Modifier::from_constant(3)]);
+///
+/// assert_eq!(damage, Damage::new("2d6+3").unwrap());
+/// ```
+impl From<Vec<Modifier>> for Damage {
+    fn from(modifiers: Vec<Modifier>) -> Self {
+        Damage {
+            parts: modifiers.into_iter().map(DamagePart::from).collect(),
+            crit_rule: CritRule::default(),
         }
     }
 }
@@ -82,25 +439,240 @@ impl Damage {
 impl RollExpression for Damage {
     type Outcome = DamageOutcome;
 
-    fn roll(&self) -> Self::Outcome {
-        DamageOutcome::new(self.0.iter().map(|part| part.roll()).collect())
+    fn roll_with_rng<R: Rng + ?Sized>(&self, rng: &mut R) -> Self::Outcome {
+        DamageOutcome::new(
+            self.parts
+                .iter()
+                .map(|part| part.with_type(part.modifier.roll_with_rng(rng)))
+                .collect(),
+        )
     }
 }
 
-impl RollExpression for DamagePart {
-    type Outcome = OutcomePart;
+/// Damage is written in a canonical form of the notation it is parsed
+/// from, so parsing the written damage gives the same `Damage` back.
+///
+/// ```
+/// use critfail::{RollExpression, Damage};
+///
+/// let damage = Damage::new("2d8 + 3 slashing - 1d4 / 2 + (1d6 + 1) * 2 fire").unwrap();
+/// assert_eq!(damage.to_string(), "2d8+3 slashing-1d4/2+(1d6+1)*2 fire");
+/// assert_eq!(Damage::new(&damage.to_string()).unwrap(), damage);
+/// ```
+///
+/// A crit rule other than the standard one is written at the end.
+///
+/// ```
+/// use critfail::{RollExpression, Damage, CritRule};
+///
+/// let damage = Damage::new("2d6+1d8+3").unwrap();
+///
+/// let max = damage.clone().with_crit_rule(CritRule::MaxPlusRoll);
+/// assert_eq!(max.to_string(), "2d6+1d8+3 crit max");
+///
+/// let only = damage.with_crit_rule(CritRule::OnlyParts(vec![1]));
+/// assert_eq!(only.to_string(), "2d6+1d8+3 crit 1");
+/// assert_eq!(Damage::new(&only.to_string()).unwrap(), only);
+/// ```
+///
+/// Empty `Damage` is written as `0`, which is parsed back as empty
+/// `Damage`.
+///
+/// ```
+/// use critfail::{RollExpression, Damage};
+///
+/// assert_eq!(Damage::default().to_string(), "0");
+/// assert_eq!(Damage::new("0").unwrap(), Damage::default());
+/// ```
+///
+/// A part without a damage type can't be written straight before a
+/// part with one, since the type would apply to both when parsed. This
+/// can only happen to `Damage` that wasn't parsed.
+impl fmt::Display for Damage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Empty damage is written as `0`, so that there's always
+        // something to parse
+        if self.parts.is_empty() {
+            write!(f, "0")?;
+        }
 
-    fn roll(&self) -> Self::Outcome {
-        match self {
-            DamagePart::Dice(num, sides) => {
-                let rolls: Vec<Score> = (0..*num)
-                    .map(|_| rand::thread_rng().gen_range(1, sides.abs() + 1))
-                    .collect();
+        for (i, part) in self.parts.iter().enumerate() {
+            if i > 0 && !part.modifier.is_negative() {
+                write!(f, "+")?;
+            }
+            write!(f, "{}", part.modifier)?;
 
-                OutcomePart::Dice(*sides, rolls)
+            // A damage type is only written after the last part of each
+            // run of parts with that type, since it applies to the parts
+            // before it when parsed.
+            let next_type = self.parts.get(i + 1).and_then(|next| next.damage_type);
+            if let Some(t) = part.damage_type.filter(|&t| Some(t) != next_type) {
+                write!(f, " {}", t)?;
             }
+        }
 
-            DamagePart::Modifier(value) => OutcomePart::Modifier(*value),
+        if self.crit_rule != CritRule::DoubleDice {
+            write!(f, " crit ")?;
+            match &self.crit_rule {
+                CritRule::DoubleDice => write!(f, "dice")?,
+                CritRule::MaxPlusRoll => write!(f, "max")?,
+                CritRule::DoubleTotal => write!(f, "total")?,
+                CritRule::OnlyParts(parts) if parts.is_empty() => write!(f, "none")?,
+                CritRule::OnlyParts(parts) => {
+                    for (i, part) in parts.iter().enumerate() {
+                        if i > 0 {
+                            write!(f, ",")?;
+                        }
+                        write!(f, "{}", part)?;
+                    }
+                }
+            }
         }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn crit_roll(expression: &str, rule: CritRule) -> DamageOutcome {
+        let damage = Damage::new(expression).unwrap().with_crit_rule(rule);
+        damage.crit_roll_with_rng(&mut StdRng::seed_from_u64(5))
+    }
+
+    #[test]
+    fn max_plus_roll() {
+        let outcome = crit_roll("2d8+3-1d4", CritRule::MaxPlusRoll);
+        let debug = format!("{:?}", outcome);
+
+        assert!(debug.starts_with("16+["), "{}", debug);
+        assert_eq!(debug.matches('[').count(), 2);
+    }
+
+    #[test]
+    fn max_plus_roll_ruled_dice() {
+        let damage = Damage::new("4d6kh3")
+            .unwrap()
+            .with_crit_rule(CritRule::MaxPlusRoll);
+        let dist = damage.crit_distribution();
+
+        assert_eq!(dist.min(), 21);
+        assert_eq!(dist.max(), 36);
+    }
+
+    #[test]
+    fn double_total() {
+        let outcome = crit_roll("2d8+3", CritRule::DoubleTotal);
+        let debug = format!("{:?}", outcome);
+
+        assert!(
+            debug.starts_with("([") && debug.ends_with("+3)*2"),
+            "{}",
+            debug
+        );
+        assert_eq!(outcome.score() % 2, 0);
+    }
+
+    #[test]
+    fn only_parts() {
+        let outcome = crit_roll("1d8+3+2d6", CritRule::OnlyParts(vec![1]));
+        let debug = format!("{:?}", outcome);
+
+        // The d8 is rolled once, and the 2d6 are rolled twice
+        assert_eq!(debug.matches('[').count(), 3);
+        assert_eq!(debug.split('+').nth(1), Some("3"));
+
+        let damage = Damage::new("1d8+3+2d6")
+            .unwrap()
+            .with_crit_rule(CritRule::OnlyParts(vec![1]));
+        let dist = damage.crit_distribution();
+        assert_eq!(dist.min(), 8);
+        assert_eq!(dist.max(), 35);
+    }
+
+    #[test]
+    fn typed_crit() {
+        let outcome = crit_roll("1d8+3 slashing + 2d6 fire", CritRule::MaxPlusRoll);
+        let types: Vec<_> = outcome
+            .scores_by_type()
+            .into_iter()
+            .map(|(t, _)| t)
+            .collect();
+
+        assert_eq!(
+            types,
+            vec![Some(DamageType::Slashing), Some(DamageType::Fire)]
+        );
+        assert!(format!("{:?}", outcome).starts_with("8+["));
+
+        let dist = Damage::new("1d8+3 slashing + 2d6 fire")
+            .unwrap()
+            .crit_distribution();
+        assert_eq!(dist.min(), 9);
+        assert_eq!(dist.max(), 43);
+    }
+
+    #[test]
+    fn double_dice_is_default() {
+        let damage = Damage::new("2d8+3-1d4").unwrap();
+
+        assert_eq!(damage.crit_rule(), &CritRule::DoubleDice);
+        assert_eq!(
+            crit_roll("2d8+3-1d4", CritRule::DoubleDice),
+            damage.crit_roll_with_rng(&mut StdRng::seed_from_u64(5))
+        );
+    }
+
+    #[test]
+    fn scaled() {
+        let dist = Damage::new("8d6/2").unwrap().distribution();
+        assert_eq!(dist.min(), 4);
+        assert_eq!(dist.max(), 24);
+
+        let dist = Damage::new("2d8-1d4/2").unwrap().distribution();
+        assert_eq!(dist.min(), 0);
+        assert_eq!(dist.max(), 16);
+
+        // The dice inside the group are doubled on a critical hit
+        let damage = Damage::new("(1d6+1)*2").unwrap();
+        let dist = damage.crit_distribution();
+        assert_eq!(dist.min(), 6);
+        assert_eq!(dist.max(), 26);
+        assert_eq!(dist.probability(7), 0.0);
+
+        let outcome = crit_roll("(1d6+1)*2", CritRule::DoubleDice);
+        let debug = format!("{:?}", outcome);
+        assert!(
+            debug.starts_with("([") && debug.ends_with("]+1)*2"),
+            "{}",
+            debug
+        );
+        assert_eq!(debug.matches('[').count(), 2);
+    }
+
+    #[test]
+    fn with_damage_type() {
+        // Only the parts after the last typed part get the new type
+        let damage = Damage::new("1d8 fire+3")
+            .unwrap()
+            .with_dice(1, -4)
+            .with_damage_type(DamageType::Cold);
+        assert_eq!(damage, Damage::new("1d8 fire+3-1d4 cold").unwrap());
+
+        // Scaled groups are typed as a whole
+        let damage = Damage::new("8d6/2")
+            .unwrap()
+            .with_damage_type(DamageType::Fire)
+            .with_damage_type(DamageType::Cold);
+        assert_eq!(damage, Damage::new("8d6/2 fire").unwrap());
+    }
+
+    #[test]
+    #[should_panic]
+    fn with_dice_zero_sides() {
+        Damage::default().with_dice(2, 0);
     }
 }
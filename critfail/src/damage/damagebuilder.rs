@@ -0,0 +1,86 @@
+use crate::{CritRule, Damage, DamageType, Score, Sides};
+
+/// This is used to create a `Damage` roll expression without parsing
+/// one from a string.
+///
+/// ```
+/// use critfail::{RollExpression, Damage, DamageBuilder, DamageType};
+///
+/// let damage = DamageBuilder::new()
+///     .dice(1, 8)
+///     .modifier(3)
+///     .damage_type(DamageType::Piercing)
+///     .dice(1, 6)
+///     .damage_type(DamageType::Poison)
+///     .build();
+///
+/// assert_eq!(damage, Damage::new("1d8+3 piercing+1d6 poison").unwrap());
+/// ```
+#[derive(Default)]
+pub struct DamageBuilder {
+    damage: Damage,
+}
+
+impl DamageBuilder {
+    /// Create a new `DamageBuilder`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Add `num` dice with `sides` sides. The dice are subtracted if
+    /// `sides` is negative. This method can be chained multiple times
+    /// for multiple sets of dice.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Damage, DamageBuilder};
+    ///
+    /// let damage = DamageBuilder::new().dice(2, 6).dice(1, -4).build();
+    ///
+    /// assert_eq!(damage, Damage::new("2d6-1d4").unwrap());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num` or `sides` is 0.
+    pub fn dice(self, num: u32, sides: Sides) -> Self {
+        Self {
+            damage: self.damage.with_dice(num, sides),
+        }
+    }
+
+    /// Add a constant modifier. This method can be chained multiple
+    /// times for multiple modifiers.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Damage, DamageBuilder};
+    ///
+    /// let damage = DamageBuilder::new().dice(1, 12).modifier(5).build();
+    ///
+    /// assert_eq!(damage, Damage::new("1d12+5").unwrap());
+    /// ```
+    pub fn modifier(self, modifier: Score) -> Self {
+        Self {
+            damage: self.damage.with_modifier(modifier),
+        }
+    }
+
+    /// Give a damage type to the parts added so far that don't have a
+    /// type yet.
+    pub fn damage_type(self, damage_type: DamageType) -> Self {
+        Self {
+            damage: self.damage.with_damage_type(damage_type),
+        }
+    }
+
+    /// Use `rule` to roll the damage on a critical hit.
+    pub fn crit_rule(self, rule: CritRule) -> Self {
+        Self {
+            damage: self.damage.with_crit_rule(rule),
+        }
+    }
+
+    /// Create a `Damage` from this builder.
+    pub fn build(self) -> Damage {
+        self.damage
+    }
+}
@@ -1,6 +1,6 @@
 use std::fmt;
 
-use crate::{ModifiersOutcome, OutcomePart, Score, Sides};
+use crate::{AdjustedDamage, DamageType, Defenses, ModifiersOutcome, OutcomePart, Score, Sides};
 
 /// The outcome of a check roll.
 ///
@@ -13,25 +13,111 @@ use crate::{ModifiersOutcome, OutcomePart, Score, Sides};
 /// let outcome: DamageOutcome = Damage::new("2d6+6").unwrap().roll();
 /// ```
 #[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DamageOutcome {
     scores: ModifiersOutcome,
+    /// The total of the scores is multiplied by this (eg. when a
+    /// critical hit doubles the total damage).
+    multiplier: Score,
 }
 
 impl DamageOutcome {
     pub(crate) fn new(scores: Vec<OutcomePart>) -> Self {
         Self {
             scores: scores.into(),
+            multiplier: 1,
+        }
+    }
+
+    /// Double the total score of this outcome.
+    pub(crate) fn doubled(self) -> Self {
+        Self {
+            multiplier: self.multiplier * 2,
+            ..self
         }
     }
 
     /// Get the score of a `DamageOutcome`.
     pub fn score(&self) -> Score {
-        self.scores.score()
+        self.scores.score() * self.multiplier
     }
 
-    // TODO: Get rid of this function once Check isn't using Damage for its rolls anymore.
-    pub(crate) fn into_modifiers(self) -> ModifiersOutcome {
+    /// Get the total score of each type of damage in this outcome, in
+    /// the order that the types first appear. Damage without a type is
+    /// totalled under `None`.
+    ///
+    /// ```
+    /// use critfail::{DamageOutcomeBuilder, DamageType};
+    ///
+    /// // To create a result that could come from rolling '1d8+3 slashing + 2d6 fire'
+    /// let outcome = DamageOutcomeBuilder::new()
+    ///     .dice(8, vec![5])
+    ///     .modifier(3)
+    ///     .damage_type(DamageType::Slashing)
+    ///     .dice(6, vec![2, 6])
+    ///     .damage_type(DamageType::Fire)
+    ///     .build();
+    ///
+    /// assert_eq!(outcome.score(), 16);
+    /// assert_eq!(
+    ///     outcome.scores_by_type(),
+    ///     vec![
+    ///         (Some(DamageType::Slashing), 8),
+    ///         (Some(DamageType::Fire), 8),
+    ///     ]
+    /// );
+    /// ```
+    pub fn scores_by_type(&self) -> Vec<(Option<DamageType>, Score)> {
         self.scores
+            .scores_by_type()
+            .into_iter()
+            .map(|(t, score)| (t, score * self.multiplier))
+            .collect()
+    }
+
+    /// Apply the resistances, vulnerabilities and immunities of a
+    /// target to this outcome.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Damage, DamageType, Defenses};
+    ///
+    /// let outcome = Damage::new("1d8+3 slashing + 2d6 fire").unwrap().roll();
+    /// let defenses = Defenses::new()
+    ///     .resistant(DamageType::Slashing)
+    ///     .immune(DamageType::Fire);
+    ///
+    /// let adjusted = outcome.apply_defenses(&defenses);
+    ///
+    /// print!("{:?}", adjusted); // Prints something like "([5]+3 slashing)/2+([2+6] fire)*0"
+    /// assert!(adjusted.score() <= 5);
+    /// ```
+    pub fn apply_defenses(&self, defenses: &Defenses) -> AdjustedDamage {
+        AdjustedDamage::new(self.scores.iter(), self.multiplier, defenses)
+    }
+
+    /// Get the parts of this outcome, in the order they were written.
+    ///
+    /// ```
+    /// use critfail::DamageOutcomeBuilder;
+    ///
+    /// // To create a result that could come from rolling '2d6+3'
+    /// let outcome = DamageOutcomeBuilder::new()
+    ///     .dice(6, vec![4, 1])
+    ///     .modifier(3)
+    ///     .build();
+    ///
+    /// let dice: Vec<_> = outcome.parts().filter_map(|p| p.sides()).collect();
+    /// assert_eq!(dice, vec![6]);
+    /// ```
+    pub fn parts(&self) -> std::slice::Iter<'_, OutcomePart> {
+        self.scores.iter()
+    }
+
+    /// Get the number the total of the parts is multiplied by. This is
+    /// 1 unless the total was doubled by a critical hit (with
+    /// `CritRule::DoubleTotal`).
+    pub fn multiplier(&self) -> Score {
+        self.multiplier
     }
 }
 
@@ -43,7 +129,11 @@ impl fmt::Display for DamageOutcome {
 
 impl fmt::Debug for DamageOutcome {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:?}", self.scores)
+        if self.multiplier == 1 {
+            write!(f, "{:?}", self.scores)
+        } else {
+            write!(f, "({:?})*{}", self.scores, self.multiplier)
+        }
     }
 }
 
@@ -129,6 +219,41 @@ impl DamageOutcomeBuilder {
         Self { scores: _scores }
     }
 
+    /// Give a damage type to the parts added so far that don't have a
+    /// type yet.
+    ///
+    /// ```
+    /// use critfail::{DamageOutcomeBuilder, DamageType};
+    ///
+    /// // To create a result that could come from rolling '2d6+1 fire'
+    /// let outcome = DamageOutcomeBuilder::new()
+    ///     .dice(6, vec![3, 4])
+    ///     .modifier(1)
+    ///     .damage_type(DamageType::Fire)
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     format!("{:?}", outcome),
+    ///     "[3+4]+1 fire"
+    /// );
+    /// ```
+    pub fn damage_type(self, damage_type: DamageType) -> Self {
+        let untyped = self
+            .scores
+            .iter()
+            .rposition(|s| s.damage_type().is_some())
+            .map(|i| i + 1)
+            .unwrap_or(0);
+
+        let mut scores = self.scores;
+        let typed: Vec<OutcomePart> = scores
+            .drain(untyped..)
+            .map(|s| OutcomePart::Typed(Box::new(s), damage_type))
+            .collect();
+        scores.extend(typed);
+        Self { scores }
+    }
+
     /// Create a `DamageOutcome` from this builder.
     pub fn build(self) -> DamageOutcome {
         DamageOutcome::new(self.scores)
@@ -138,6 +263,7 @@ impl DamageOutcomeBuilder {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{DieRoll, Scale};
     use OutcomePart::Dice as D;
     use OutcomePart::Modifier as M;
 
@@ -168,6 +294,121 @@ mod tests {
         assert_eq!(format!("{:?}", r), "[1+2+3]-2");
     }
 
+    #[test]
+    fn parts() {
+        let mut dropped = DieRoll::new(1);
+        dropped.set_dropped();
+        let r = DamageOutcome::new(vec![
+            OutcomePart::RuledDice(-6, vec![DieRoll::new(4), dropped]),
+            OutcomePart::Typed(Box::new(D(8, vec![7])), DamageType::Fire),
+            OutcomePart::Scaled(vec![D(4, vec![3]), M(1)], Scale::div(2)),
+            M(2),
+        ]);
+
+        let sides: Vec<_> = r.parts().map(|p| p.sides()).collect();
+        assert_eq!(sides, vec![Some(-6), Some(8), None, None]);
+
+        let values: Vec<_> = r.parts().map(|p| p.values()).collect();
+        assert_eq!(values, vec![vec![4, 1], vec![7], vec![], vec![2]]);
+
+        assert_eq!(r.multiplier(), 1);
+        assert_eq!(r.doubled().multiplier(), 2);
+    }
+
+    #[test]
+    fn dropped_dice() {
+        let mut dropped = DieRoll::new(1);
+        dropped.set_dropped();
+        let r = DamageOutcome::new(vec![
+            OutcomePart::RuledDice(6, vec![DieRoll::new(4), dropped, DieRoll::new(6)]),
+            M(1),
+        ]);
+
+        assert_eq!(r.score(), 11);
+        assert_eq!(format!("{}", r), "11");
+        assert_eq!(format!("{:?}", r), "[4+(1)+6]+1");
+    }
+
+    #[test]
+    fn exploded_dice() {
+        let mut exploded = DieRoll::new(6);
+        exploded.set_explosion(DieRoll::new(3));
+        let r = DamageOutcome::new(vec![
+            OutcomePart::RuledDice(6, vec![exploded, DieRoll::new(2)]),
+            M(1),
+        ]);
+
+        assert_eq!(r.score(), 12);
+        assert_eq!(format!("{:?}", r), "[6!+3+2]+1");
+    }
+
+    #[test]
+    fn rerolled_dice() {
+        let mut rerolled = DieRoll::new(1);
+        rerolled.reroll(2);
+        rerolled.reroll(5);
+        let r = DamageOutcome::new(vec![OutcomePart::RuledDice(
+            8,
+            vec![rerolled, DieRoll::new(4)],
+        )]);
+
+        assert_eq!(r.score(), 9);
+        assert_eq!(format!("{:?}", r), "[1→2→5+4]");
+    }
+
+    #[test]
+    fn raised_dice() {
+        let mut raised = DieRoll::new(1);
+        raised.raise_to(2);
+        let r = DamageOutcome::new(vec![OutcomePart::RuledDice(
+            6,
+            vec![raised, DieRoll::new(5)],
+        )]);
+
+        assert_eq!(r.score(), 7);
+        assert_eq!(format!("{:?}", r), "[1→2+5]");
+    }
+
+    #[test]
+    fn doubled() {
+        let r = DamageOutcome::new(vec![D(8, vec![3, 5]), M(3)]).doubled();
+        assert_eq!(r.score(), 22);
+        assert_eq!(format!("{}", r), "22");
+        assert_eq!(format!("{:?}", r), "([3+5]+3)*2");
+    }
+
+    #[test]
+    fn typed() {
+        let typed = |part, t| OutcomePart::Typed(Box::new(part), t);
+        let r = DamageOutcome::new(vec![
+            typed(D(8, vec![5]), DamageType::Slashing),
+            typed(M(3), DamageType::Slashing),
+            typed(D(6, vec![2, 6]), DamageType::Fire),
+            M(-1),
+        ]);
+
+        assert_eq!(r.score(), 15);
+        assert_eq!(format!("{}", r), "15");
+        assert_eq!(format!("{:?}", r), "[5]+3 slashing+[2+6] fire-1");
+        assert_eq!(
+            r.scores_by_type(),
+            vec![
+                (Some(DamageType::Slashing), 8),
+                (Some(DamageType::Fire), 8),
+                (None, -1)
+            ]
+        );
+    }
+
+    #[test]
+    fn typed_doubled() {
+        let typed = |part, t| OutcomePart::Typed(Box::new(part), t);
+        let r = DamageOutcome::new(vec![typed(D(6, vec![2, 6]), DamageType::Fire)]).doubled();
+
+        assert_eq!(format!("{:?}", r), "([2+6] fire)*2");
+        assert_eq!(r.scores_by_type(), vec![(Some(DamageType::Fire), 16)]);
+    }
+
     #[test]
     fn negative_dice() {
         let r = DamageOutcome::new(vec![D(6, vec![4, 1, 6]), M(4), D(-4, vec![3, 1])]);
@@ -175,4 +416,31 @@ mod tests {
         assert_eq!(format!("{}", r), "11");
         assert_eq!(format!("{:?}", r), "[4+1+6]+4-[3+1]");
     }
+
+    #[test]
+    fn scaled() {
+        let scaled = |parts, scale| OutcomePart::Scaled(parts, scale);
+
+        let r = DamageOutcome::new(vec![scaled(vec![D(6, vec![3, 5]), M(3)], Scale::Mul(2))]);
+        assert_eq!(r.score(), 22);
+        assert_eq!(format!("{:?}", r), "([3+5]+3)*2");
+
+        let r = DamageOutcome::new(vec![scaled(vec![D(6, vec![2, 5, 6])], Scale::div(2))]);
+        assert_eq!(r.score(), 6);
+        assert_eq!(format!("{:?}", r), "[2+5+6]/2");
+
+        let r = DamageOutcome::new(vec![
+            D(8, vec![4, 7]),
+            scaled(vec![D(4, vec![3])], Scale::div(-2)),
+        ]);
+        assert_eq!(r.score(), 10);
+        assert_eq!(format!("{:?}", r), "[4+7]-[3]/2");
+
+        let r = DamageOutcome::new(vec![scaled(
+            vec![scaled(vec![D(6, vec![5])], Scale::Mul(2))],
+            Scale::div(3),
+        )]);
+        assert_eq!(r.score(), 3);
+        assert_eq!(format!("{:?}", r), "[5]*2/3");
+    }
 }
@@ -1,79 +1,58 @@
 use std::str::FromStr;
 
-use regex::Regex;
+use crate::damage::DamagePart;
+use crate::modifier::modifierparse;
+use crate::parse::{self, DamageExpr};
+use crate::{Damage, ParseError};
 
-use crate::ParseError;
-use crate::{damage::DamagePart, Damage};
-use crate::{Score, Sides};
-
-lazy_static! {
-    static ref DICE_RE: Regex = Regex::new("^(-?)([0-9]+)d([0-9]+)$").unwrap();
-}
-lazy_static! {
-    static ref MODIFIER_RE: Regex = Regex::new("^(-?)([0-9]+)$").unwrap();
-}
-
-impl FromStr for DamagePart {
+impl FromStr for Damage {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some(cap) = DICE_RE.captures(s) {
-            let sign = if &cap[1] == "-" { -1 } else { 1 };
-            Ok(DamagePart::Dice(
-                cap[2].parse().map_err(|_| ParseError::new(s))?,
-                (cap[3].parse::<Sides>().map_err(|_| ParseError::new(s))?) * sign,
-            ))
-        } else if let Some(cap) = MODIFIER_RE.captures(s) {
-            let sign = if &cap[1] == "-" { -1 } else { 1 };
-            Ok(DamagePart::Modifier(
-                cap[2].parse::<Score>().map_err(|_| ParseError::new(s))? * sign,
-            ))
-        } else {
-            Err(ParseError::new(s))
-        }
+        Ok(parse::damage(s)?.into())
     }
 }
 
-impl FromStr for Damage {
-    type Err = ParseError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut i = 0;
-        let mut result = vec![];
+impl From<DamageExpr> for Damage {
+    fn from(expr: DamageExpr) -> Self {
+        let mut result: Vec<DamagePart> = vec![];
+        // The number of parts at the start of `result` that have a type
+        let mut typed = 0;
+        let crit_rule = expr.crit_rule.clone().unwrap_or_default();
 
-        while i < s.len() {
-            // Grab everything up to the next +/-, and see if it's a DamagePart
-            let end = s[i + 1..]
-                .find(['+', '-'])
-                .map(|n| (i + 1) + n)
-                .unwrap_or_else(|| s.len());
-
-            let part: DamagePart = s[i..end].parse()?;
-            result.push(part);
-
-            // Update the index
-            i = end;
+        for (modifier, damage_type) in modifierparse::flatten(expr) {
+            // A damage type applies to the parts before it that don't
+            // have a type yet.
+            if let Some(t) = damage_type {
+                for untyped in result[typed..].iter_mut() {
+                    untyped.damage_type = Some(t);
+                }
+                typed = result.len() + 1;
+            }
+            result.push(DamagePart {
+                modifier,
+                damage_type,
+            });
+        }
 
-            // If we stopped on a +, skip it
-            if s[i..].starts_with('+') {
-                i += 1;
+        // A lone `0` is how empty damage is written
+        if let [part] = result.as_slice() {
+            if part.damage_type.is_none() && part.modifier.constant() == Some(0) {
+                result.clear();
             }
         }
 
-        Ok(Damage(result))
+        Damage {
+            parts: result,
+            crit_rule,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    macro_rules! test_damagepart {
-        ($string:expr, $damage:expr) => {
-            let d = $string.parse::<DamagePart>().unwrap();
-            assert_eq!(d, $damage);
-        };
-    }
+    use crate::{CritRule, DamageType, DiceRules, KeepRule, Modifier, Scale, Score, Sides};
 
     macro_rules! test_damage {
         ($string:expr, $damage:expr) => {
@@ -82,66 +61,202 @@ mod tests {
         };
     }
 
-    mod parse_damagepart {
+    mod parse_damage {
         use super::*;
+
+        fn dice(num: u32, sides: Sides) -> Modifier {
+            Modifier::from_dice(num, sides)
+        }
+
+        fn constant(value: Score) -> Modifier {
+            Modifier::from_constant(value)
+        }
+
+        fn damage(parts: Vec<DamagePart>) -> Damage {
+            Damage {
+                parts,
+                crit_rule: CritRule::default(),
+            }
+        }
+
+        fn typed_part(modifier: Modifier, damage_type: DamageType) -> DamagePart {
+            DamagePart {
+                modifier,
+                damage_type: Some(damage_type),
+            }
+        }
+
         #[test]
-        fn simple_damage() {
-            test_damagepart!("2d8", DamagePart::Dice(2, 8));
-            test_damagepart!("1d12", DamagePart::Dice(1, 12));
-            test_damagepart!("3d6", DamagePart::Dice(3, 6));
-            test_damagepart!("421d314159", DamagePart::Dice(421, 314_159));
+        fn normal_damage() {
+            test_damage!("2d6+3", Damage::from(vec![dice(2, 6), constant(3)]));
+            test_damage!("8d4-4", Damage::from(vec![dice(8, 4), constant(-4)]));
+            test_damage!("-2d8+3", Damage::from(vec![dice(2, -8), constant(3)]));
+            test_damage!(
+                "3d12+3d6-1d4+2-3",
+                Damage::from(vec![
+                    dice(3, 12),
+                    dice(3, 6),
+                    dice(1, -4),
+                    constant(2),
+                    constant(-3)
+                ])
+            );
         }
 
         #[test]
-        fn negative_damage() {
-            test_damagepart!("-2d8", DamagePart::Dice(2, -8));
-            test_damagepart!("-1d12", DamagePart::Dice(1, -12));
-            test_damagepart!("-3d6", DamagePart::Dice(3, -6));
-            test_damagepart!("-421d314159", DamagePart::Dice(421, -314_159));
+        fn typed() {
+            test_damage!(
+                "1d8+3 slashing + 2d6 fire",
+                damage(vec![
+                    typed_part(dice(1, 8), DamageType::Slashing),
+                    typed_part(constant(3), DamageType::Slashing),
+                    typed_part(dice(2, 6), DamageType::Fire),
+                ])
+            );
+            test_damage!(
+                "2d6 fire+3",
+                damage(vec![
+                    typed_part(dice(2, 6), DamageType::Fire),
+                    constant(3).into()
+                ])
+            );
+            test_damage!(
+                "1d6 piercing - 1d4 cold",
+                damage(vec![
+                    typed_part(dice(1, 6), DamageType::Piercing),
+                    typed_part(dice(1, -4), DamageType::Cold),
+                ])
+            );
         }
 
         #[test]
-        fn modifier() {
-            test_damagepart!("4", DamagePart::Modifier(4));
-            test_damagepart!("-3", DamagePart::Modifier(-3));
-            test_damagepart!("26", DamagePart::Modifier(26));
-            test_damagepart!("-129", DamagePart::Modifier(-129));
+        fn keep() {
+            let keep = |keep| DiceRules {
+                keep: Some(keep),
+                ..Default::default()
+            };
+
+            test_damage!(
+                "4d6kh3+2",
+                Damage::from(vec![
+                    Modifier::from_ruled_dice(4, 6, keep(KeepRule::KeepHighest(3))),
+                    constant(2)
+                ])
+            );
         }
 
         #[test]
-        fn invalid() {
-            assert!("2q4".parse::<DamagePart>().is_err());
-            assert!("d20".parse::<DamagePart>().is_err());
-            assert!("r+3".parse::<DamagePart>().is_err());
-            assert!("d-3".parse::<DamagePart>().is_err());
-            assert!("d".parse::<DamagePart>().is_err());
-            assert!("".parse::<DamagePart>().is_err());
-            assert!("2d6+3".parse::<DamagePart>().is_err());
+        fn whitespace() {
+            test_damage!("2d6 + 3", Damage::from(vec![dice(2, 6), constant(3)]));
+            test_damage!(
+                " 1d8 -  1d4 + 2 ",
+                Damage::from(vec![dice(1, 8), dice(1, -4), constant(2)])
+            );
+            test_damage!("2d6+-3", Damage::from(vec![dice(2, 6), constant(-3)]));
+            test_damage!("", Damage::from(vec![]));
         }
-    }
 
-    mod parse_damage {
-        use super::*;
-        use DamagePart::Dice as D;
-        use DamagePart::Modifier as M;
+        #[test]
+        fn parentheses() {
+            test_damage!("(2d6+3)", Damage::from(vec![dice(2, 6), constant(3)]));
+            test_damage!(
+                "1d8-(1d4-2)",
+                Damage::from(vec![dice(1, 8), dice(1, -4), constant(2)])
+            );
+            test_damage!(
+                "(1d6+2) fire + 3",
+                damage(vec![
+                    typed_part(dice(1, 6), DamageType::Fire),
+                    typed_part(constant(2), DamageType::Fire),
+                    constant(3).into()
+                ])
+            );
+            test_damage!(
+                "(1d6 cold + 2) + 1d4 fire",
+                damage(vec![
+                    typed_part(dice(1, 6), DamageType::Cold),
+                    typed_part(constant(2), DamageType::Fire),
+                    typed_part(dice(1, 4), DamageType::Fire),
+                ])
+            );
+        }
 
         #[test]
-        fn normal_damage() {
-            test_damage!("2d6+3", Damage(vec![D(2, 6), M(3)]));
-            test_damage!("8d4-4", Damage(vec![D(8, 4), M(-4)]));
-            test_damage!("-2d8+3", Damage(vec![D(2, -8), M(3)]));
+        fn scaled() {
+            let scaled = Modifier::from_scaled;
+
             test_damage!(
-                "3d12+3d6-1d4+2-3",
-                Damage(vec![D(3, 12), D(3, 6), D(1, -4), M(2), M(-3)])
+                "8d6/2",
+                Damage::from(vec![scaled(vec![dice(8, 6)], Scale::div(2))])
+            );
+            test_damage!(
+                "(2d6+3)*2",
+                Damage::from(vec![scaled(vec![dice(2, 6), constant(3)], Scale::Mul(2))])
+            );
+            test_damage!(
+                "2d8 - 1d4/2",
+                Damage::from(vec![dice(2, 8), scaled(vec![dice(1, 4)], Scale::div(-2))])
+            );
+            test_damage!(
+                "1d6*2/3",
+                Damage::from(vec![scaled(
+                    vec![scaled(vec![dice(1, 6)], Scale::Mul(2))],
+                    Scale::div(3)
+                )])
+            );
+            test_damage!(
+                "1d8 + 8d6/2 fire",
+                damage(vec![
+                    typed_part(dice(1, 8), DamageType::Fire),
+                    typed_part(scaled(vec![dice(8, 6)], Scale::div(2)), DamageType::Fire),
+                ])
             );
         }
 
+        #[test]
+        fn crit_rule() {
+            let crit = |s: &str| s.parse::<Damage>().unwrap().crit_rule;
+
+            assert_eq!(crit("1d8+3"), CritRule::DoubleDice);
+            assert_eq!(crit("1d8+3 crit dice"), CritRule::DoubleDice);
+            assert_eq!(crit("1d8+3 slashing crit max"), CritRule::MaxPlusRoll);
+            assert_eq!(crit("1d8+3 CRIT Total"), CritRule::DoubleTotal);
+            assert_eq!(crit("1d8+3+2d6 crit 0, 1"), CritRule::OnlyParts(vec![0, 1]));
+            assert_eq!(crit("1d8-(2d6+3)*2 crit 1"), CritRule::OnlyParts(vec![1]));
+            assert_eq!(crit("1d8+3 crit none"), CritRule::OnlyParts(vec![]));
+            assert_eq!(crit("crit max"), CritRule::MaxPlusRoll);
+        }
+
         #[test]
         fn invalid() {
             assert!("+3d6".parse::<Damage>().is_err());
+            assert!("2d6 crit".parse::<Damage>().is_err());
+            assert!("2d6 crit maximum".parse::<Damage>().is_err());
+            assert!("2d6 crit 0,".parse::<Damage>().is_err());
+            assert!("1d6 crit 1,1".parse::<Damage>().is_err());
+            assert!("1d6 crit 1".parse::<Damage>().is_err());
+            assert!("1d6+(2d6+3)*2 crit 2".parse::<Damage>().is_err());
+            assert!("3 crit 0".parse::<Damage>().is_err());
+            assert!("2d6crit max".parse::<Damage>().is_err());
+            assert!("2d6 crit max crit total".parse::<Damage>().is_err());
+            assert!("(2d6 crit max)+3".parse::<Damage>().is_err());
             assert!("3d6+2q".parse::<Damage>().is_err());
             assert!("3d6-2q".parse::<Damage>().is_err());
             assert!("3d6++4".parse::<Damage>().is_err());
+            assert!("3d6 holy".parse::<Damage>().is_err());
+            assert!("3d6 fire cold".parse::<Damage>().is_err());
+            assert!("3d6 fire + fire".parse::<Damage>().is_err());
+            assert!("(2d6+3".parse::<Damage>().is_err());
+            assert!("2d6+3)".parse::<Damage>().is_err());
+            assert!("()".parse::<Damage>().is_err());
+            assert!("(1d6 fire) cold".parse::<Damage>().is_err());
+            assert!("2 d6".parse::<Damage>().is_err());
+            assert!("2d6--3".parse::<Damage>().is_err());
+            assert!("8d6/0".parse::<Damage>().is_err());
+            assert!("8d6/".parse::<Damage>().is_err());
+            assert!("8d6*-2".parse::<Damage>().is_err());
+            assert!("8d6 fire/2".parse::<Damage>().is_err());
+            assert!("(1d6 fire + 2)*2".parse::<Damage>().is_err());
         }
     }
 }
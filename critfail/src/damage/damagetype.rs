@@ -0,0 +1,123 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::{ParseError, ParseErrorKind};
+
+/// The type of some damage, eg. the `fire` in `2d6 fire`.
+///
+/// ```
+/// use critfail::DamageType;
+///
+/// assert_eq!("fire".parse::<DamageType>().unwrap(), DamageType::Fire);
+/// assert_eq!(DamageType::Slashing.to_string(), "slashing");
+/// ```
+#[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DamageType {
+    /// Acid damage.
+    Acid,
+    /// Bludgeoning damage.
+    Bludgeoning,
+    /// Cold damage.
+    Cold,
+    /// Fire damage.
+    Fire,
+    /// Force damage.
+    Force,
+    /// Lightning damage.
+    Lightning,
+    /// Necrotic damage.
+    Necrotic,
+    /// Piercing damage.
+    Piercing,
+    /// Poison damage.
+    Poison,
+    /// Psychic damage.
+    Psychic,
+    /// Radiant damage.
+    Radiant,
+    /// Slashing damage.
+    Slashing,
+    /// Thunder damage.
+    Thunder,
+}
+
+impl DamageType {
+    /// Every damage type, in alphabetical order.
+    pub const ALL: [DamageType; 13] = [
+        DamageType::Acid,
+        DamageType::Bludgeoning,
+        DamageType::Cold,
+        DamageType::Fire,
+        DamageType::Force,
+        DamageType::Lightning,
+        DamageType::Necrotic,
+        DamageType::Piercing,
+        DamageType::Poison,
+        DamageType::Psychic,
+        DamageType::Radiant,
+        DamageType::Slashing,
+        DamageType::Thunder,
+    ];
+
+    /// Get the name of this damage type, as it is written in a roll
+    /// expression.
+    pub fn name(self) -> &'static str {
+        match self {
+            DamageType::Acid => "acid",
+            DamageType::Bludgeoning => "bludgeoning",
+            DamageType::Cold => "cold",
+            DamageType::Fire => "fire",
+            DamageType::Force => "force",
+            DamageType::Lightning => "lightning",
+            DamageType::Necrotic => "necrotic",
+            DamageType::Piercing => "piercing",
+            DamageType::Poison => "poison",
+            DamageType::Psychic => "psychic",
+            DamageType::Radiant => "radiant",
+            DamageType::Slashing => "slashing",
+            DamageType::Thunder => "thunder",
+        }
+    }
+}
+
+impl FromStr for DamageType {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DamageType::ALL
+            .iter()
+            .copied()
+            .find(|t| t.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<_> = DamageType::ALL.iter().map(|t| t.name()).collect();
+                ParseError::whole(ParseErrorKind::UnknownToken, s).expecting(&names)
+            })
+    }
+}
+
+impl fmt::Display for DamageType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        for t in DamageType::ALL.iter() {
+            assert_eq!(t.to_string().parse::<DamageType>().unwrap(), *t);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        assert!("".parse::<DamageType>().is_err());
+        assert!("fir".parse::<DamageType>().is_err());
+        assert!("fire ".parse::<DamageType>().is_err());
+        assert!("holy".parse::<DamageType>().is_err());
+    }
+}
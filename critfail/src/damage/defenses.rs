@@ -0,0 +1,285 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::{util, DamageType, ModifiersOutcome, OutcomePart, Score};
+
+/// How a target is affected by a type of damage.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Defense {
+    /// The target takes half damage (rounded down).
+    Resistant,
+    /// The target takes double damage.
+    Vulnerable,
+    /// The target takes no damage.
+    Immune,
+}
+
+impl Defense {
+    /// Get the damage a target with this defense takes from `score`
+    /// damage.
+    ///
+    /// ```
+    /// use critfail::Defense;
+    ///
+    /// assert_eq!(Defense::Resistant.apply(7), 3);
+    /// assert_eq!(Defense::Vulnerable.apply(7), 14);
+    /// assert_eq!(Defense::Immune.apply(7), 0);
+    /// ```
+    pub fn apply(self, score: Score) -> Score {
+        match self {
+            Defense::Resistant => score.div_euclid(2),
+            Defense::Vulnerable => score * 2,
+            Defense::Immune => 0,
+        }
+    }
+}
+
+/// The resistances, vulnerabilities and immunities of a target.
+///
+/// ```
+/// use critfail::{Defenses, Defense, DamageType};
+///
+/// let defenses = Defenses::new()
+///     .resistant(DamageType::Fire)
+///     .immune(DamageType::Poison);
+///
+/// assert_eq!(defenses.get(DamageType::Fire), Some(Defense::Resistant));
+/// assert_eq!(defenses.get(DamageType::Cold), None);
+/// ```
+#[derive(PartialEq, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Defenses(BTreeMap<DamageType, Defense>);
+
+impl Defenses {
+    /// Create a new `Defenses` with no resistances, vulnerabilities or
+    /// immunities.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set the defense against `damage_type`, replacing any defense
+    /// that was already set for it.
+    pub fn with(self, damage_type: DamageType, defense: Defense) -> Self {
+        let mut defenses = self.0;
+        defenses.insert(damage_type, defense);
+        Self(defenses)
+    }
+
+    /// Make the target resistant to `damage_type`.
+    pub fn resistant(self, damage_type: DamageType) -> Self {
+        self.with(damage_type, Defense::Resistant)
+    }
+
+    /// Make the target vulnerable to `damage_type`.
+    pub fn vulnerable(self, damage_type: DamageType) -> Self {
+        self.with(damage_type, Defense::Vulnerable)
+    }
+
+    /// Make the target immune to `damage_type`.
+    pub fn immune(self, damage_type: DamageType) -> Self {
+        self.with(damage_type, Defense::Immune)
+    }
+
+    /// Get the defense against `damage_type`, if there is one.
+    pub fn get(&self, damage_type: DamageType) -> Option<Defense> {
+        self.0.get(&damage_type).copied()
+    }
+}
+
+/// A `DamageOutcome` after the defenses of a target have been applied.
+///
+/// This is normally constructed by calling `apply_defenses()` on a
+/// `DamageOutcome`. The damage of each type is totalled before the
+/// defense against it is applied, so resistance only rounds down once
+/// per damage type.
+///
+/// ```
+/// use critfail::{DamageOutcomeBuilder, DamageType, Defenses};
+///
+/// // To create a result that could come from rolling '1d8+3 slashing + 2d6 fire'
+/// let outcome = DamageOutcomeBuilder::new()
+///     .dice(8, vec![5])
+///     .modifier(3)
+///     .damage_type(DamageType::Slashing)
+///     .dice(6, vec![2, 5])
+///     .damage_type(DamageType::Fire)
+///     .build();
+///
+/// let adjusted = outcome.apply_defenses(&Defenses::new().resistant(DamageType::Fire));
+///
+/// assert_eq!(adjusted.score(), 11);
+/// assert_eq!(format!("{}", adjusted), "11");
+/// assert_eq!(format!("{:?}", adjusted), "[5]+3 slashing+([2+5] fire)/2");
+/// ```
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdjustedDamage {
+    groups: Vec<TypeGroup>,
+}
+
+/// All the parts of a `DamageOutcome` with the same damage type.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct TypeGroup {
+    damage_type: Option<DamageType>,
+    parts: ModifiersOutcome,
+    multiplier: Score,
+    defense: Option<Defense>,
+}
+
+impl AdjustedDamage {
+    /// Split `parts` up by damage type, in the order that the types
+    /// first appear.
+    pub(crate) fn new<'a, I>(parts: I, multiplier: Score, defenses: &Defenses) -> Self
+    where
+        I: IntoIterator<Item = &'a OutcomePart>,
+    {
+        let mut types: Vec<(Option<DamageType>, Vec<OutcomePart>)> = Vec::new();
+
+        for part in parts {
+            let t = part.damage_type();
+            match types.iter_mut().find(|(other, _)| *other == t) {
+                Some((_, parts)) => parts.push(part.clone()),
+                None => types.push((t, vec![part.clone()])),
+            }
+        }
+
+        let groups = types
+            .into_iter()
+            .map(|(damage_type, parts)| TypeGroup {
+                damage_type,
+                parts: parts.into(),
+                multiplier,
+                defense: damage_type.and_then(|t| defenses.get(t)),
+            })
+            .collect();
+
+        Self { groups }
+    }
+
+    /// Get the total damage after the defenses have been applied.
+    pub fn score(&self) -> Score {
+        self.groups.iter().map(|g| g.score()).sum()
+    }
+
+    /// Get the total of each type of damage after the defenses have
+    /// been applied, in the order that the types first appear. Damage
+    /// without a type is totalled under `None`.
+    pub fn scores_by_type(&self) -> Vec<(Option<DamageType>, Score)> {
+        self.groups
+            .iter()
+            .map(|g| (g.damage_type, g.score()))
+            .collect()
+    }
+}
+
+impl TypeGroup {
+    fn score(&self) -> Score {
+        let score = self.parts.score() * self.multiplier;
+        match self.defense {
+            Some(defense) => defense.apply(score),
+            None => score,
+        }
+    }
+}
+
+impl fmt::Display for AdjustedDamage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.score())
+    }
+}
+
+impl fmt::Debug for AdjustedDamage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        util::write_string_sum(f, self.groups.iter().map(|g| format!("{:?}", g)))
+    }
+}
+
+impl fmt::Debug for TypeGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = format!("{:?}", self.parts);
+        if self.multiplier != 1 {
+            parts = format!("({})*{}", parts, self.multiplier);
+        }
+
+        match self.defense {
+            Some(Defense::Resistant) => write!(f, "({})/2", parts),
+            Some(Defense::Vulnerable) => write!(f, "({})*2", parts),
+            Some(Defense::Immune) => write!(f, "({})*0", parts),
+            None => write!(f, "{}", parts),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DamageOutcome;
+    use crate::DamageType::*;
+    use OutcomePart::Dice as D;
+    use OutcomePart::Modifier as M;
+
+    fn typed(part: OutcomePart, t: DamageType) -> OutcomePart {
+        OutcomePart::Typed(Box::new(part), t)
+    }
+
+    #[test]
+    fn no_defenses() {
+        let r = DamageOutcome::new(vec![typed(D(6, vec![2, 5]), Fire), M(3)]);
+        let adjusted = r.apply_defenses(&Defenses::new());
+
+        assert_eq!(adjusted.score(), r.score());
+        assert_eq!(format!("{:?}", adjusted), "[2+5] fire+3");
+    }
+
+    #[test]
+    fn resistance_rounds_down_once_per_type() {
+        // 3 + 2 is 5 fire damage, which halves to 2 (not 1 + 1)
+        let r = DamageOutcome::new(vec![
+            typed(D(6, vec![3]), Fire),
+            typed(M(1), Cold),
+            typed(D(4, vec![2]), Fire),
+        ]);
+        let adjusted = r.apply_defenses(&Defenses::new().resistant(Fire));
+
+        assert_eq!(adjusted.score(), 3);
+        assert_eq!(
+            adjusted.scores_by_type(),
+            vec![(Some(Fire), 2), (Some(Cold), 1)]
+        );
+        assert_eq!(format!("{:?}", adjusted), "([3]+[2] fire)/2+1 cold");
+    }
+
+    #[test]
+    fn vulnerable_and_immune() {
+        let r = DamageOutcome::new(vec![
+            typed(D(8, vec![4]), Radiant),
+            typed(D(6, vec![5, 1]), Poison),
+            M(2),
+        ]);
+        let defenses = Defenses::new().vulnerable(Radiant).immune(Poison);
+        let adjusted = r.apply_defenses(&defenses);
+
+        assert_eq!(adjusted.score(), 10);
+        assert_eq!(
+            format!("{:?}", adjusted),
+            "([4] radiant)*2+([5+1] poison)*0+2"
+        );
+    }
+
+    #[test]
+    fn doubled() {
+        let r = DamageOutcome::new(vec![typed(D(6, vec![3]), Fire), M(2)]).doubled();
+        let adjusted = r.apply_defenses(&Defenses::new().resistant(Fire));
+
+        assert_eq!(adjusted.score(), 7);
+        assert_eq!(format!("{:?}", adjusted), "(([3] fire)*2)/2+(2)*2");
+    }
+
+    #[test]
+    fn replace_defense() {
+        let defenses = Defenses::new().resistant(Fire).immune(Fire);
+        assert_eq!(defenses.get(Fire), Some(Defense::Immune));
+    }
+}
@@ -0,0 +1,294 @@
+use crate::{AdvState, Score, Sides};
+
+/// The exact probability distribution of the score of a roll
+/// expression.
+///
+/// This is normally constructed by calling `distribution()` on a roll
+/// expression.
+///
+/// ```
+/// use critfail::{RollExpression, Damage};
+///
+/// let dist = Damage::new("2d6+1").unwrap().distribution();
+///
+/// assert_eq!(dist.min(), 3);
+/// assert_eq!(dist.max(), 13);
+/// assert!((dist.mean() - 8.0).abs() < 1e-9);
+/// assert!((dist.probability(8) - 6.0 / 36.0).abs() < 1e-12);
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Distribution {
+    /// The score that `probs[0]` refers to.
+    offset: Score,
+    /// The probability of each score, starting at `offset`.
+    probs: Vec<f64>,
+}
+
+impl Distribution {
+    /// A distribution that always produces `value`.
+    pub(crate) fn constant(value: Score) -> Self {
+        Self {
+            offset: value,
+            probs: vec![1.0],
+        }
+    }
+
+    /// The distribution of a single die. Dice with negative sides
+    /// produce negative scores.
+    pub(crate) fn die(sides: Sides) -> Self {
+        let n = sides.unsigned_abs() as usize;
+        let offset = if sides < 0 { sides } else { 1 };
+
+        Self {
+            offset,
+            probs: vec![1.0 / n as f64; n],
+        }
+    }
+
+    /// Build a distribution from a list of scores and their
+    /// probabilities. Scores may appear more than once.
+    pub(crate) fn from_scores<I>(scores: I) -> Self
+    where
+        I: IntoIterator<Item = (Score, f64)>,
+    {
+        let scores: Vec<(Score, f64)> = scores.into_iter().collect();
+        let offset = scores.iter().map(|(s, _)| *s).min().unwrap_or(0);
+        let max = scores.iter().map(|(s, _)| *s).max().unwrap_or(0);
+
+        let mut probs = vec![0.0; (max - offset) as usize + 1];
+        for (score, p) in scores {
+            probs[(score - offset) as usize] += p;
+        }
+
+        Self { offset, probs }
+    }
+
+    /// The distribution of the d20 that is kept when rolling a check
+    /// with the advantage state `adv`.
+    pub(crate) fn d20(adv: AdvState) -> Self {
+        let probs = (1..=20)
+            .map(|face: i32| match adv {
+                // The chance that the highest of two d20s is `face`
+                AdvState::Advantage => (face.pow(2) - (face - 1).pow(2)) as f64 / 400.0,
+                AdvState::Neutral => 1.0 / 20.0,
+                // The chance that the lowest of two d20s is `face`
+                AdvState::Disadvantage => ((21 - face).pow(2) - (20 - face).pow(2)) as f64 / 400.0,
+            })
+            .collect();
+
+        Self { offset: 1, probs }
+    }
+
+    /// The distribution of the sum of a score from `self` and a score
+    /// from `other`.
+    pub(crate) fn convolve(&self, other: &Self) -> Self {
+        let mut probs = vec![0.0; self.probs.len() + other.probs.len() - 1];
+
+        for (i, p) in self.probs.iter().enumerate() {
+            for (j, q) in other.probs.iter().enumerate() {
+                probs[i + j] += p * q;
+            }
+        }
+
+        Self {
+            offset: self.offset + other.offset,
+            probs,
+        }
+    }
+
+    /// The distribution of the negative of a score from `self`.
+    pub(crate) fn negate(&self) -> Self {
+        Self {
+            offset: -(self.offset + self.probs.len() as Score - 1),
+            probs: self.probs.iter().rev().copied().collect(),
+        }
+    }
+
+    /// The distribution of a score from `self` multiplied by `factor`.
+    pub(crate) fn scale(&self, factor: Score) -> Self {
+        Self::from_scores(self.iter().map(|(score, p)| (score * factor, p)))
+    }
+
+    /// The distribution of the sum of `n` independent scores from
+    /// `self`.
+    pub(crate) fn repeat(&self, n: u32) -> Self {
+        (0..n).fold(Self::constant(0), |acc, _| acc.convolve(self))
+    }
+
+    /// The lowest score with a non-zero probability.
+    pub fn min(&self) -> Score {
+        self.iter()
+            .next()
+            .map(|(score, _)| score)
+            .unwrap_or(self.offset)
+    }
+
+    /// The highest score with a non-zero probability.
+    pub fn max(&self) -> Score {
+        self.iter()
+            .last()
+            .map(|(score, _)| score)
+            .unwrap_or(self.offset)
+    }
+
+    /// The expected value of the score.
+    pub fn mean(&self) -> f64 {
+        self.iter().map(|(score, p)| score as f64 * p).sum()
+    }
+
+    /// The variance of the score.
+    pub fn variance(&self) -> f64 {
+        let mean = self.mean();
+        self.iter()
+            .map(|(score, p)| (score as f64 - mean).powi(2) * p)
+            .sum()
+    }
+
+    /// The probability of getting exactly `score`.
+    pub fn probability(&self, score: Score) -> f64 {
+        let i = score - self.offset;
+        if i < 0 {
+            0.0
+        } else {
+            self.probs.get(i as usize).copied().unwrap_or(0.0)
+        }
+    }
+
+    /// The probability of getting `score` or higher.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Damage};
+    ///
+    /// let dist = Damage::new("1d4").unwrap().distribution();
+    ///
+    /// assert_eq!(dist.at_least(3), 0.5);
+    /// assert_eq!(dist.at_least(1), 1.0);
+    /// assert_eq!(dist.at_least(5), 0.0);
+    /// ```
+    pub fn at_least(&self, score: Score) -> f64 {
+        self.iter()
+            .filter(|(s, _)| *s >= score)
+            .map(|(_, p)| p)
+            .sum()
+    }
+
+    /// Iterate over every possible score and its probability, in order
+    /// of increasing score. Scores that can't be rolled are skipped.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Damage};
+    ///
+    /// let dist = Damage::new("1d2+1").unwrap().distribution();
+    ///
+    /// assert_eq!(dist.iter().collect::<Vec<_>>(), vec![(2, 0.5), (3, 0.5)]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (Score, f64)> + '_ {
+        let offset = self.offset;
+        self.probs
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| **p > 0.0)
+            .map(move |(i, p)| (offset + i as Score, *p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn constant() {
+        let d = Distribution::constant(-3);
+        assert_eq!(d.min(), -3);
+        assert_eq!(d.max(), -3);
+        assert_close(d.mean(), -3.0);
+        assert_close(d.variance(), 0.0);
+        assert_close(d.probability(-3), 1.0);
+        assert_close(d.probability(0), 0.0);
+    }
+
+    #[test]
+    fn single_die() {
+        let d = Distribution::die(6);
+        assert_eq!(d.min(), 1);
+        assert_eq!(d.max(), 6);
+        assert_close(d.mean(), 3.5);
+        assert_close(d.variance(), 35.0 / 12.0);
+        assert_close(d.probability(4), 1.0 / 6.0);
+    }
+
+    #[test]
+    fn negative_die() {
+        let d = Distribution::die(-4);
+        assert_eq!(d.min(), -4);
+        assert_eq!(d.max(), -1);
+        assert_close(d.mean(), -2.5);
+        assert_close(d.probability(-2), 0.25);
+        assert_close(d.probability(2), 0.0);
+    }
+
+    #[test]
+    fn sum_of_dice() {
+        let d = Distribution::die(6).repeat(2);
+        assert_eq!(d.min(), 2);
+        assert_eq!(d.max(), 12);
+        assert_close(d.mean(), 7.0);
+        assert_close(d.probability(7), 6.0 / 36.0);
+        assert_close(d.probability(2), 1.0 / 36.0);
+        assert_close(d.iter().map(|(_, p)| p).sum(), 1.0);
+    }
+
+    #[test]
+    fn d20() {
+        let neutral = Distribution::d20(AdvState::Neutral);
+        let adv = Distribution::d20(AdvState::Advantage);
+        let dis = Distribution::d20(AdvState::Disadvantage);
+
+        assert_close(neutral.mean(), 10.5);
+        assert_close(adv.mean(), 13.825);
+        assert_close(dis.mean(), 7.175);
+
+        assert_close(adv.probability(20), 39.0 / 400.0);
+        assert_close(adv.probability(1), 1.0 / 400.0);
+        assert_close(dis.probability(1), 39.0 / 400.0);
+        assert_close(dis.probability(20), 1.0 / 400.0);
+
+        for d in &[neutral, adv, dis] {
+            assert_close(d.iter().map(|(_, p)| p).sum(), 1.0);
+        }
+    }
+
+    #[test]
+    fn negate() {
+        assert_eq!(Distribution::die(6).negate(), Distribution::die(-6));
+        assert_eq!(
+            Distribution::constant(2).negate(),
+            Distribution::constant(-2)
+        );
+    }
+
+    #[test]
+    fn scale() {
+        let d = Distribution::die(4).scale(2);
+        assert_eq!(
+            d.iter().collect::<Vec<_>>(),
+            vec![(2, 0.25), (4, 0.25), (6, 0.25), (8, 0.25)]
+        );
+        assert_close(d.mean(), 5.0);
+    }
+
+    #[test]
+    fn from_scores() {
+        let d = Distribution::from_scores(vec![(3, 0.25), (1, 0.5), (3, 0.25)]);
+        assert_eq!(d.iter().collect::<Vec<_>>(), vec![(1, 0.5), (3, 0.5)]);
+    }
+
+    #[test]
+    fn empty_repeat() {
+        assert_eq!(Distribution::die(8).repeat(0), Distribution::constant(0));
+    }
+}
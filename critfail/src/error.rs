@@ -1,34 +1,229 @@
 use std::error::Error;
 use std::fmt;
+use std::ops::Range;
+
+/// The kind of problem that was found while parsing a roll expression.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum ParseErrorKind {
+    /// The expression was empty.
+    Empty,
+    /// Something that isn't part of any roll expression, eg. the `q`
+    /// in `r+q`.
+    UnknownToken,
+    /// A set of dice with no dice in it, eg. `0d6`.
+    EmptyDiceCount,
+    /// A die with zero sides, eg. `2d0`.
+    ZeroSidedDie,
+    /// An operator without anything after it, eg. `2d6+` or `8d6/`.
+    DanglingOperator,
+    /// An attack with more than one `?`, eg. `r+5?1d8?1d6`.
+    MultipleQuestionMarks,
+    /// A number that is out of range, eg. the crit range in `r+5c25`.
+    InvalidNumber,
+    /// A dice rule that can't be used, eg. a rule that is given twice
+    /// or a die that would explode forever.
+    InvalidDiceRule,
+    /// Something that is valid on its own but not where it was used,
+    /// eg. a damage type in a check.
+    Unexpected,
+}
+
+impl ParseErrorKind {
+    fn description(self) -> &'static str {
+        match self {
+            ParseErrorKind::Empty => "empty expression",
+            ParseErrorKind::UnknownToken => "unknown token",
+            ParseErrorKind::EmptyDiceCount => "missing number of dice",
+            ParseErrorKind::ZeroSidedDie => "zero-sided die",
+            ParseErrorKind::DanglingOperator => "dangling operator",
+            ParseErrorKind::MultipleQuestionMarks => "more than one",
+            ParseErrorKind::InvalidNumber => "invalid number",
+            ParseErrorKind::InvalidDiceRule => "invalid dice rule",
+            ParseErrorKind::Unexpected => "unexpected",
+        }
+    }
+}
 
-#[derive(Debug, Clone)]
 /// Represents an error parsing a roll expression.
+///
+/// A `ParseError` knows what went wrong, where in the expression it
+/// went wrong, and what would have been valid there instead.
+///
+/// ```
+/// use critfail::{ParseErrorKind, Roll, RollExpression};
+///
+/// let err = Roll::new("r+5?1d0").unwrap_err();
+///
+/// assert_eq!(err.kind(), ParseErrorKind::ZeroSidedDie);
+/// assert_eq!(err.span(), 4..7);
+/// assert_eq!(err.underline(), "    ^^^");
+/// ```
+#[derive(PartialEq, Eq, Debug, Clone)]
 pub struct ParseError {
-    details: String,
+    kind: ParseErrorKind,
+    span: Range<usize>,
+    found: String,
+    expected: Vec<&'static str>,
 }
 
 impl ParseError {
-    pub(crate) fn new(msg: &str) -> ParseError {
+    /// Create an error of kind `kind` for the text `s[span]`.
+    pub(crate) fn new(kind: ParseErrorKind, s: &str, span: Range<usize>) -> ParseError {
         ParseError {
-            details: msg.to_string(),
+            kind,
+            found: s.get(span.clone()).unwrap_or_default().to_string(),
+            span,
+            expected: vec![],
         }
     }
+
+    /// Create an error of kind `kind` that covers all of `s`.
+    pub(crate) fn whole(kind: ParseErrorKind, s: &str) -> ParseError {
+        ParseError::new(kind, s, 0..s.len())
+    }
+
+    /// Set the list of things that would have been valid instead.
+    pub(crate) fn expecting(mut self, expected: &[&'static str]) -> ParseError {
+        self.expected = expected.to_vec();
+        self
+    }
+
+    /// Move the span of this error `offset` bytes to the right. This is
+    /// used when the error came from parsing a slice of a larger
+    /// expression.
+    pub(crate) fn shifted(mut self, offset: usize) -> ParseError {
+        self.span = self.span.start + offset..self.span.end + offset;
+        self
+    }
+
+    /// Get the kind of problem that was found.
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+
+    /// Get the byte range of the expression that caused the error.
+    ///
+    /// The range can be empty, eg. when something is missing at the
+    /// end of the expression.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// Get the part of the expression that caused the error.
+    pub fn found(&self) -> &str {
+        &self.found
+    }
+
+    /// Get the things that would have been valid instead of the part
+    /// of the expression that caused the error.
+    pub fn expected(&self) -> &[&'static str] {
+        &self.expected
+    }
+
+    /// Get a line of spaces and carets that underlines the span of this
+    /// error when printed beneath the expression.
+    ///
+    /// An empty span is underlined with a single caret.
+    pub fn underline(&self) -> String {
+        let len = (self.span.end - self.span.start).max(1);
+        format!("{}{}", " ".repeat(self.span.start), "^".repeat(len))
+    }
 }
 
 impl Default for ParseError {
     fn default() -> Self {
-        ParseError { details: "".into() }
+        ParseError::whole(ParseErrorKind::Empty, "")
     }
 }
 
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.details)
+        write!(f, "{}", self.kind.description())?;
+        if !self.found.is_empty() {
+            write!(f, " `{}`", self.found)?;
+        }
+
+        match self.expected.split_last() {
+            None => Ok(()),
+            Some((last, [])) => write!(f, ", expected {}", last),
+            Some((last, rest)) => write!(f, ", expected {} or {}", rest.join(", "), last),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Represents a variable in a roll expression that wasn't given a
+/// value.
+///
+/// ```
+/// use critfail::{RollExpression, Roll};
+/// use std::collections::HashMap;
+///
+/// let roll = Roll::new("r+$str+$prof").unwrap();
+/// let context: HashMap<String, i32> = vec![("str".to_string(), 3)].into_iter().collect();
+///
+/// let err = roll.bind(&context).unwrap_err();
+/// assert_eq!(err.name(), "prof");
+/// assert_eq!(format!("{}", err), "no value for `$prof`");
+/// ```
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct UnboundVariable {
+    name: String,
+}
+
+impl UnboundVariable {
+    pub(crate) fn new(name: &str) -> UnboundVariable {
+        UnboundVariable {
+            name: name.to_string(),
+        }
+    }
+
+    /// Get the name of the variable, without the `$`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for UnboundVariable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "no value for `${}`", self.name)
     }
 }
 
-impl Error for ParseError {
-    fn description(&self) -> &str {
-        &self.details
+impl Error for UnboundVariable {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let err = ParseError::new(ParseErrorKind::UnknownToken, "r+q", 2..3);
+        assert_eq!(format!("{}", err), "unknown token `q`");
+
+        let err = err.expecting(&["a number"]);
+        assert_eq!(format!("{}", err), "unknown token `q`, expected a number");
+
+        let err = err.expecting(&["a number", "dice", "a damage type"]);
+        assert_eq!(
+            format!("{}", err),
+            "unknown token `q`, expected a number, dice or a damage type"
+        );
+
+        let err = ParseError::new(ParseErrorKind::Empty, "", 0..0);
+        assert_eq!(format!("{}", err), "empty expression");
+    }
+
+    #[test]
+    fn shifted() {
+        let err = ParseError::new(ParseErrorKind::ZeroSidedDie, "1d0", 0..3).shifted(4);
+        assert_eq!(err.span(), 4..7);
+        assert_eq!(err.found(), "1d0");
+        assert_eq!(err.underline(), "    ^^^");
+
+        let err = ParseError::new(ParseErrorKind::UnknownToken, "r+5?", 4..4);
+        assert_eq!(err.underline(), "    ^");
     }
 }
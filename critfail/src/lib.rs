@@ -22,44 +22,80 @@
 //! let attack_outcome = attack.roll();
 //! print!("{}", attack_outcome); // eg. "10 ? 16"
 //! print!("{:?}", attack_outcome); // eg. "(9)+1 ? [6+6]+4"
+//!
+//! let save = Roll::new("save DC15 dex +3 : 8d6 half").unwrap();
+//! let save_outcome = save.roll();
+//! print!("{}", save_outcome); // eg. "Saved (17) : 14"
+//! print!("{:?}", save_outcome); // eg. "(14)+3 vs 15 : ([3+4+6+2+5+1+3+4])/2"
 //! ```
 //!
 //! In order to handle the outcome of a `Roll` programatically, roll
-//! expressions are split into `Check` rolls, `Damage` rolls, and
-//! `Attack` rolls, each with their own outcome type which provides
-//! methods for determining the score and makeup of the results for
-//! each.
+//! expressions are split into `Check` rolls, `Damage` rolls, `Attack`
+//! rolls, and `Save` rolls, each with their own outcome type which
+//! provides methods for determining the score and makeup of the results
+//! for each.
+//!
+//! Instead of rolling, `Damage::distribution()` calculates the exact
+//! probability of every possible score of a damage roll,
+//! `Check::chance()` calculates the chance of a check beating a DC, and
+//! `Attack::expected_damage()` calculates the average damage of an
+//! attack against an armor class.
+//!
+//! Roll expressions can also be made without parsing a string, using
+//! `CheckBuilder`, `DamageBuilder` and `AttackBuilder`, and changed
+//! with methods like `Check::with_modifier()` or
+//! `Attack::with_advantage()`.
+//!
+//! `Character` holds the ability scores and proficiencies from a
+//! character sheet, and builds the `Check` for any of their ability
+//! checks, skill checks or saving throws.
 //!
 //! # Features
 //! * `wasm-bindgen`: Enable this when compiling for wasm32 targets, or
 //!   random number generation won't work.
+//! * `serde`: Implement `Serialize` and `Deserialize` for the roll
+//!   expressions and their outcomes. Outcomes keep every die that was
+//!   rolled, not just the score. Deserialized expressions are checked
+//!   the same way as parsed ones.
 #![warn(missing_docs)]
 #![doc(
     test(attr(deny(warnings))),
     test(attr(allow(unused_variables, unused_mut)))
 )]
 
-#[macro_use]
-extern crate lazy_static;
-
+use rand::Rng;
 use std::fmt;
 use std::str::FromStr;
 
+mod ability;
 mod attack;
+mod character;
 mod check;
 mod damage;
+mod distribution;
 mod error;
 mod modifier;
+mod parse;
 mod roll;
+mod save;
 mod util;
 
-pub use attack::{Attack, AttackOutcome, AttackOutcomeBuilder};
-pub use check::{AdvState, Check, CheckOutcome, CheckOutcomeBuilder, CritScore};
-pub use damage::{Damage, DamageOutcome, DamageOutcomeBuilder};
-pub use error::ParseError;
+pub use ability::Ability;
+pub use attack::{Attack, AttackBuilder, AttackOutcome, AttackOutcomeBuilder};
+pub use character::{Character, Proficiency, Skill};
+pub use check::{
+    AdvState, Check, CheckBuilder, CheckChance, CheckOutcome, CheckOutcomeBuilder, CritScore,
+};
+pub use damage::{
+    AdjustedDamage, CritRule, Damage, DamageBuilder, DamageOutcome, DamageOutcomeBuilder,
+    DamageType, Defense, Defenses,
+};
+pub use distribution::Distribution;
+pub use error::{ParseError, ParseErrorKind, UnboundVariable};
 pub(crate) use modifier::ModifiersOutcome;
-pub use modifier::OutcomePart;
-pub use roll::{Roll, RollOutcome};
+pub use modifier::{DiceRules, DieRoll, KeepRule, Modifier, OutcomePart, Reroll, Scale};
+pub use roll::{RepeatedOutcome, Roll, RollOutcome};
+pub use save::{Save, SaveEffect, SaveOutcome};
 
 /// The number type that is used when reporting the score of a roll
 pub type Score = i32;
@@ -81,8 +117,39 @@ pub trait RollExpression: Sized + FromStr<Err = ParseError> {
     }
 
     /// Roll the dice and return an outcome.
-    fn roll(&self) -> Self::Outcome;
+    ///
+    /// Variables that haven't been given a value with `bind()` count
+    /// as 0.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Check};
+    ///
+    /// let outcome = Check::new("r+$str").unwrap().roll();
+    /// assert!(outcome.score() <= 20);
+    /// ```
+    fn roll(&self) -> Self::Outcome {
+        self.roll_with_rng(&mut rand::thread_rng())
+    }
+
+    /// Roll the dice using `rng` as the source of randomness.
+    ///
+    /// This is useful when rolls need to be reproducible, eg. by using
+    /// a seeded random number generator.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Roll};
+    /// use rand::{rngs::StdRng, SeedableRng};
+    ///
+    /// let roll = Roll::new("r+3?2d8+4").unwrap();
+    ///
+    /// let first = roll.roll_with_rng(&mut StdRng::seed_from_u64(42));
+    /// let second = roll.roll_with_rng(&mut StdRng::seed_from_u64(42));
+    ///
+    /// assert_eq!(format!("{:?}", first), format!("{:?}", second));
+    /// ```
+    ///
+    /// Unbound variables count as 0, as they do for `roll()`.
+    fn roll_with_rng<R: Rng + ?Sized>(&self, rng: &mut R) -> Self::Outcome;
 
-    // TODO: Add an error type for parsing rollexps
-    // TODO: Add with_options() and builder() methods.
+    // TODO: Add with_options() methods.
 }
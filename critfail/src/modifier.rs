@@ -1,104 +1,617 @@
-use crate::{util, Score, Sides};
+use rand::Rng;
+use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
 use std::fmt;
+use std::num::NonZeroI32;
+use std::ops::Neg;
 
-/// Internally used wrapper struct for a collection of OutcomeParts.
-#[derive(Clone, PartialEq)]
-pub(crate) struct ModifiersOutcome {
-    // OPTIMIZE: Cache sum in this struct
-    scores: Vec<OutcomePart>,
+use crate::RollExpression;
+use crate::{util, CritRule, Distribution, Score, Sides, UnboundVariable};
+
+pub use dicerules::{DiceRules, KeepRule, Reroll};
+pub(crate) use modifieroutcome::ModifiersOutcome;
+pub use modifieroutcome::{DieRoll, OutcomePart};
+
+mod dicerules;
+mod modifieroutcome;
+pub(crate) mod modifierparse;
+
+/// One part of the modifier of a check or the damage of a roll, eg. the
+/// `1d4` or the `2` in `r+2+1d4`.
+///
+/// Modifiers are usually parsed, but they can also be made from their
+/// parts. A modifier can be negated with `-`.
+///
+/// ```
+/// use critfail::{RollExpression, Check, Modifier};
+///
+/// let check = Check::new("r+2+1d4").unwrap();
+///
+/// assert_eq!(
+///     check.modifiers(),
+///     &[Modifier::from_constant(2), Modifier::from_dice(1, 4)]
+/// );
+/// assert_eq!(Modifier::new("-1d6").unwrap(), -Modifier::from_dice(1, 6));
+/// assert_eq!(Modifier::new("-1d6").unwrap().dice(), Some((1, -6)));
+/// ```
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "Kind", into = "Kind"))]
+pub struct Modifier(Kind);
+
+/// The different kinds of modifier. This is kept private so that a
+/// `Modifier` can only be made in a form that can be written and parsed
+/// back.
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Kind {
+    /// A number of dice with a number of sides, eg. `2d6`. The dice are
+    /// subtracted if the number of sides is negative.
+    Dice(u32, Sides),
+    /// Dice with extra rules, eg. `4d6kh3` or `2d6r<2`. The rules are
+    /// never empty.
+    RuledDice(u32, Sides, DiceRules),
+    /// A constant number.
+    Constant(Score),
+    /// A variable like `$str`. It counts as 0 until it is given a value
+    /// with `bind()`.
+    Variable { name: String, negative: bool },
+    /// A group of modifiers whose total is multiplied or divided, eg.
+    /// `(2d6+3)*2` or `8d6/2`. The group is never empty.
+    Scaled(Vec<Modifier>, Scale),
+}
+
+/// How the total of a group of damage is scaled, eg. the `*2` in
+/// `(2d6+3)*2` or the `/2` in `8d6/2`.
+///
+/// A negative factor also negates the total, so the `-1d4/2` in
+/// `2d8-1d4/2` is divided by `-2`.
+///
+/// ```
+/// use critfail::{RollExpression, Damage};
+///
+/// let dist = Damage::new("7/2+(1d4+1)*3").unwrap().distribution();
+///
+/// // Division rounds down
+/// assert_eq!(dist.min(), 9);
+/// assert_eq!(dist.max(), 18);
+/// ```
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Scale {
+    /// Multiply the total by a number.
+    Mul(Score),
+    /// Divide the total by a number, rounding down.
+    Div(NonZeroI32),
+}
+
+impl Scale {
+    /// Create a scale that divides by `divisor`.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Damage, Modifier, Scale};
+    ///
+    /// let half = Modifier::from_scaled(vec![Modifier::from_dice(8, 6)], Scale::div(2));
+    ///
+    /// assert_eq!(Damage::from(vec![half]), Damage::new("8d6/2").unwrap());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `divisor` is 0.
+    pub fn div(divisor: Score) -> Self {
+        Scale::Div(NonZeroI32::new(divisor).expect("can't divide by 0"))
+    }
+
+    /// Get the number the total is multiplied or divided by.
+    pub(crate) fn factor(self) -> Score {
+        match self {
+            Scale::Mul(factor) => factor,
+            Scale::Div(divisor) => divisor.get(),
+        }
+    }
+
+    /// Scale `total` by this factor.
+    pub(crate) fn apply(self, total: Score) -> Score {
+        match self {
+            Scale::Mul(factor) => total * factor,
+            // `div_euclid` rounds towards negative infinity for positive
+            // divisors, and rounds the negated total down for negative
+            // ones.
+            Scale::Div(divisor) => total.div_euclid(divisor.get()),
+        }
+    }
+
+    /// Get this scale with its factor negated.
+    pub(crate) fn negated(self) -> Self {
+        match self {
+            Scale::Mul(factor) => Scale::Mul(-factor),
+            Scale::Div(divisor) => Scale::div(-divisor.get()),
+        }
+    }
+
+    fn distribution(self, dist: &Distribution) -> Distribution {
+        Distribution::from_scores(dist.iter().map(|(score, p)| (self.apply(score), p)))
+    }
 }
 
-impl ModifiersOutcome {
-    pub fn score(&self) -> Score {
-        self.scores.iter().map(|s| s.score()).sum()
+impl Modifier {
+    /// Create a modifier of `num` dice with `sides` sides, eg. `2d6`.
+    /// The dice are subtracted if `sides` is negative.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num` or `sides` is 0.
+    pub fn from_dice(num: u32, sides: Sides) -> Self {
+        assert!(num != 0, "can't add 0 dice");
+        assert!(sides != 0, "can't add a die with 0 sides");
+        Modifier(Kind::Dice(num, sides))
+    }
+
+    /// Create a modifier of dice with extra rules. Dice without any
+    /// rules are plain dice.
+    pub(crate) fn from_ruled_dice(num: u32, sides: Sides, rules: DiceRules) -> Self {
+        if rules == DiceRules::default() {
+            Modifier::from_dice(num, sides)
+        } else {
+            Modifier(Kind::RuledDice(num, sides, rules))
+        }
+    }
+
+    /// Create a constant modifier.
+    pub fn from_constant(value: Score) -> Self {
+        Modifier(Kind::Constant(value))
+    }
+
+    /// Create a variable called `name` (without the `$`), which is
+    /// given a value with `bind()`.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Modifier};
+    ///
+    /// assert_eq!(Modifier::from_variable("str"), Modifier::new("$str").unwrap());
+    /// assert_eq!(-Modifier::from_variable("str"), Modifier::new("-$str").unwrap());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `name` is empty, or has characters other than ASCII
+    /// letters, digits and `_`.
+    pub fn from_variable(name: &str) -> Self {
+        assert!(is_variable_name(name), "invalid variable name `{}`", name);
+        Modifier(Kind::Variable {
+            name: name.to_string(),
+            negative: false,
+        })
+    }
+
+    /// Create a group of modifiers whose total is multiplied or divided,
+    /// eg. `(2d6+3)*2`.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Modifier, Scale};
+    ///
+    /// let double = Modifier::from_scaled(
+    ///     vec![Modifier::from_dice(2, 6), Modifier::from_constant(3)],
+    ///     Scale::Mul(2),
+    /// );
+    ///
+    /// assert_eq!(double, Modifier::new("(2d6+3)*2").unwrap());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `parts` is empty.
+    pub fn from_scaled(parts: Vec<Modifier>, scale: Scale) -> Self {
+        assert!(!parts.is_empty(), "can't scale an empty group");
+        Modifier(Kind::Scaled(parts, scale))
+    }
+
+    /// Get the number of dice and their number of sides, if this
+    /// modifier is a set of dice. The number of sides is negative if
+    /// the dice are subtracted.
+    pub fn dice(&self) -> Option<(u32, Sides)> {
+        match self.0 {
+            Kind::Dice(num, sides) | Kind::RuledDice(num, sides, _) => Some((num, sides)),
+            _ => None,
+        }
+    }
+
+    /// Get the extra rules of this modifier, if it is a set of dice
+    /// with rules like `4d6kh3`.
+    pub fn rules(&self) -> Option<&DiceRules> {
+        match &self.0 {
+            Kind::RuledDice(_, _, rules) => Some(rules),
+            _ => None,
+        }
+    }
+
+    /// Get the value of this modifier, if it is a constant.
+    pub fn constant(&self) -> Option<Score> {
+        match self.0 {
+            Kind::Constant(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Get the name of this modifier (without the `$`), if it is a
+    /// variable.
+    pub fn variable(&self) -> Option<&str> {
+        match &self.0 {
+            Kind::Variable { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Get the group of modifiers and how their total is scaled, if
+    /// this modifier is scaled.
+    pub fn scaled(&self) -> Option<(&[Modifier], Scale)> {
+        match &self.0 {
+            Kind::Scaled(parts, scale) => Some((parts, *scale)),
+            _ => None,
+        }
+    }
+
+    /// Whether this modifier is subtracted, which is written with a `-`
+    /// in front of it.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Modifier};
+    ///
+    /// assert!(Modifier::new("-$str").unwrap().is_negative());
+    /// assert!(!Modifier::new("2d6").unwrap().is_negative());
+    /// ```
+    pub fn is_negative(&self) -> bool {
+        match &self.0 {
+            Kind::Dice(_, sides) | Kind::RuledDice(_, sides, _) => *sides < 0,
+            Kind::Constant(value) => *value < 0,
+            Kind::Scaled(_, scale) => scale.factor() < 0,
+            Kind::Variable { negative, .. } => *negative,
+        }
+    }
+
+    /// Count the sets of dice in this modifier, including the ones
+    /// inside groups.
+    #[cfg(feature = "serde")]
+    pub(crate) fn dice_sets(&self) -> usize {
+        match &self.0 {
+            Kind::Dice(..) | Kind::RuledDice(..) => 1,
+            Kind::Scaled(parts, _) => parts.iter().map(Modifier::dice_sets).sum(),
+            _ => 0,
+        }
+    }
+
+    /// Add the names of the variables in this modifier to `names`.
+    pub(crate) fn add_variables<'a>(&'a self, names: &mut Vec<&'a str>) {
+        match &self.0 {
+            Kind::Variable { name, .. } => util::merge_names(names, vec![name]),
+            Kind::Scaled(parts, _) => parts.iter().for_each(|p| p.add_variables(names)),
+            _ => (),
+        }
+    }
+
+    /// Replace the variables in this modifier with their values in
+    /// `context`.
+    pub(crate) fn bind(&self, context: &HashMap<String, Score>) -> Result<Self, UnboundVariable> {
+        Ok(match &self.0 {
+            Kind::Variable { name, negative } => {
+                let value = *context
+                    .get(name)
+                    .ok_or_else(|| UnboundVariable::new(name))?;
+                Modifier::from_constant(if *negative { -value } else { value })
+            }
+            Kind::Scaled(parts, scale) => Modifier(Kind::Scaled(
+                parts
+                    .iter()
+                    .map(|part| part.bind(context))
+                    .collect::<Result<_, _>>()?,
+                *scale,
+            )),
+            _ => self.clone(),
+        })
+    }
+
+    /// Get the highest score this modifier can roll, not counting
+    /// explosions.
+    fn max(&self) -> Score {
+        match &self.0 {
+            Kind::Dice(num, sides) => *num as Score * sides,
+            Kind::RuledDice(num, sides, rules) => rules.max(*num, *sides),
+            Kind::Constant(value) => *value,
+            Kind::Scaled(parts, scale) => scale.apply(parts.iter().map(|p| p.max()).sum()),
+            Kind::Variable { .. } => 0,
+        }
+    }
+
+    /// Calculate the exact probability distribution of the score of
+    /// this modifier.
+    pub(crate) fn distribution(&self) -> Distribution {
+        match &self.0 {
+            Kind::Dice(num, sides) => Distribution::die(*sides).repeat(*num),
+            Kind::RuledDice(num, sides, rules) => rules.distribution(*num, *sides),
+            Kind::Constant(value) => Distribution::constant(*value),
+            Kind::Scaled(parts, scale) => {
+                scale.distribution(&sum(parts.iter().map(Modifier::distribution)))
+            }
+            Kind::Variable { .. } => Distribution::constant(0),
+        }
+    }
+
+    /// Whether `rule` gives this modifier extra dice on a critical hit.
+    /// `dice` is the number of sets of dice before this modifier, and
+    /// is counted past it.
+    fn is_crit(&self, rule: &CritRule, dice: &mut usize) -> bool {
+        let sides = match &self.0 {
+            Kind::Dice(_, sides) | Kind::RuledDice(_, sides, _) => *sides,
+            _ => return false,
+        };
+
+        *dice += 1;
+        let crit = match rule {
+            CritRule::OnlyParts(parts) => parts.contains(&(*dice - 1)),
+            CritRule::DoubleTotal => false,
+            _ => true,
+        };
+        crit && sides > 0
     }
 
-    pub(crate) fn into_inner(self) -> Vec<OutcomePart> {
-        self.scores
+    /// Roll this modifier as a critical hit using `rule`. Dice inside
+    /// groups are doubled within the group, so `(2d6+3)*2` rolls as
+    /// `(4d6+3)*2`.
+    pub(crate) fn crit_roll<R: Rng + ?Sized>(
+        &self,
+        rule: &CritRule,
+        dice: &mut usize,
+        rng: &mut R,
+    ) -> Vec<OutcomePart> {
+        match &self.0 {
+            Kind::Scaled(parts, scale) => {
+                let mut inner = Vec::new();
+                for part in parts {
+                    inner.extend(part.crit_roll(rule, dice, rng));
+                }
+                vec![OutcomePart::Scaled(inner, *scale)]
+            }
+            _ => {
+                let mut result = Vec::new();
+                if self.is_crit(rule, dice) {
+                    match rule {
+                        CritRule::MaxPlusRoll => result.push(OutcomePart::Modifier(self.max())),
+                        _ => result.push(self.roll_with_rng(rng)),
+                    }
+                }
+                result.push(self.roll_with_rng(rng));
+                result
+            }
+        }
+    }
+
+    /// Calculate the distribution of this modifier when it is rolled as
+    /// a critical hit using `rule`.
+    pub(crate) fn crit_distribution(&self, rule: &CritRule, dice: &mut usize) -> Distribution {
+        match &self.0 {
+            Kind::Scaled(parts, scale) => scale.distribution(&sum(parts
+                .iter()
+                .map(|part| part.crit_distribution(rule, dice)))),
+            _ => {
+                let dist = self.distribution();
+                match (self.is_crit(rule, dice), rule) {
+                    (false, _) => dist,
+                    (true, CritRule::MaxPlusRoll) => {
+                        dist.convolve(&Distribution::constant(self.max()))
+                    }
+                    (true, _) => dist.convolve(&dist),
+                }
+            }
+        }
     }
 }
 
-impl IntoIterator for ModifiersOutcome {
-    type Item = OutcomePart;
-    type IntoIter = std::vec::IntoIter<OutcomePart>;
+/// Get the distribution of the sum of independent scores.
+pub(crate) fn sum<I: Iterator<Item = Distribution>>(dists: I) -> Distribution {
+    dists.fold(Distribution::constant(0), |acc, dist| acc.convolve(&dist))
+}
 
-    fn into_iter(self) -> std::vec::IntoIter<OutcomePart> {
-        self.scores.into_iter()
+/// Write `parts` as a sum, eg. `2d6+3-1d4`.
+pub(crate) fn write_sum(f: &mut fmt::Formatter, parts: &[Modifier]) -> fmt::Result {
+    for (i, part) in parts.iter().enumerate() {
+        if i > 0 && !part.is_negative() {
+            write!(f, "+")?;
+        }
+        write!(f, "{}", part)?;
     }
+
+    Ok(())
 }
 
-impl From<Vec<OutcomePart>> for ModifiersOutcome {
-    fn from(scores: Vec<OutcomePart>) -> Self {
-        Self { scores }
+impl RollExpression for Modifier {
+    type Outcome = OutcomePart;
+
+    fn roll_with_rng<R: Rng + ?Sized>(&self, rng: &mut R) -> Self::Outcome {
+        match &self.0 {
+            Kind::Dice(num, sides) => {
+                let rolls: Vec<Score> = (0..*num)
+                    .map(|_| rng.gen_range(1, sides.abs() + 1))
+                    .collect();
+
+                OutcomePart::Dice(*sides, rolls)
+            }
+
+            Kind::RuledDice(num, sides, rules) => {
+                OutcomePart::RuledDice(*sides, rules.roll(*num, *sides, rng))
+            }
+
+            Kind::Constant(value) => OutcomePart::Modifier(*value),
+
+            Kind::Scaled(parts, scale) => OutcomePart::Scaled(
+                parts.iter().map(|part| part.roll_with_rng(rng)).collect(),
+                *scale,
+            ),
+
+            Kind::Variable { .. } => OutcomePart::Modifier(0),
+        }
     }
 }
 
-/// Enum representing the different kinds of values that can be returned
-/// for damage or the modifier on a check.
-#[derive(Clone, PartialEq)]
-pub enum OutcomePart {
-    /// The result of rolling a set of dice.
-    ///
-    /// For example, If I rolled 3d6 and got a 4, a 2, and a 1, that
-    /// would be `Dice(6, vec![4,2,1])`.
-    Dice(Sides, Vec<Score>),
-    /// A constant modifier value.
-    Modifier(Score),
+/// Whether `name` can be written as the name of a variable.
+fn is_variable_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
 }
 
-impl OutcomePart {
-    /// Get the score of this `OutcomePart`.
-    ///
-    /// Either the sum of the die rolls, or the value of the modifier.
-    pub fn score(&self) -> Score {
-        match self {
-            Self::Dice(sides, d) => {
-                let sum: Score = d.iter().sum();
-                if *sides < 0 {
-                    -sum
+/// Deserialized modifiers are checked the same way as the ones made
+/// with the constructors, so that they can always be rolled and written.
+#[cfg(feature = "serde")]
+impl TryFrom<Kind> for Modifier {
+    type Error = &'static str;
+
+    fn try_from(kind: Kind) -> Result<Self, Self::Error> {
+        match kind {
+            Kind::Dice(0, _) | Kind::RuledDice(0, _, _) => Err("can't add 0 dice"),
+            Kind::Dice(_, 0) | Kind::RuledDice(_, 0, _) => Err("can't add a die with 0 sides"),
+            Kind::RuledDice(num, sides, rules) => {
+                if rules.fits(num, sides) {
+                    Ok(Modifier::from_ruled_dice(num, sides, rules))
                 } else {
-                    sum
+                    Err("invalid dice rules")
                 }
             }
-            Self::Modifier(m) => *m,
+            Kind::Variable { ref name, .. } if !is_variable_name(name) => {
+                Err("invalid variable name")
+            }
+            Kind::Scaled(ref parts, _) if parts.is_empty() => Err("can't scale an empty group"),
+            kind => Ok(Modifier(kind)),
         }
     }
 }
 
-impl fmt::Display for ModifiersOutcome {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.score())
+#[cfg(feature = "serde")]
+impl From<Modifier> for Kind {
+    fn from(modifier: Modifier) -> Self {
+        modifier.0
     }
 }
 
-impl fmt::Debug for ModifiersOutcome {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let scores = self.scores.iter().map(|s| format!("{:?}", s));
-        util::write_string_sum(f, scores)
+/// Negating a modifier flips whether it is added or subtracted.
+///
+/// ```
+/// use critfail::{RollExpression, Modifier};
+///
+/// assert_eq!(-Modifier::new("(2d6+3)*2").unwrap(), Modifier::new("-(2d6+3)*2").unwrap());
+/// assert_eq!(-Modifier::new("-3").unwrap(), Modifier::from_constant(3));
+/// ```
+impl Neg for Modifier {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Modifier(match self.0 {
+            Kind::Dice(num, sides) => Kind::Dice(num, -sides),
+            Kind::RuledDice(num, sides, rules) => Kind::RuledDice(num, -sides, rules),
+            Kind::Constant(value) => Kind::Constant(-value),
+            Kind::Variable { name, negative } => Kind::Variable {
+                name,
+                negative: !negative,
+            },
+            Kind::Scaled(parts, scale) => Kind::Scaled(parts, scale.negated()),
+        })
     }
 }
 
-impl fmt::Display for OutcomePart {
+/// Modifiers are written in the notation they are parsed from, eg.
+/// `-1d4`, `4d6kh3` or `(2d6+3)*2`.
+impl fmt::Display for Modifier {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.score())
+        let sign = if self.is_negative() { "-" } else { "" };
+
+        match &self.0 {
+            Kind::Dice(num, sides) => write!(f, "{}{}d{}", sign, num, sides.abs()),
+            Kind::RuledDice(num, sides, rules) => {
+                write!(f, "{}{}d{}", sign, num, sides.abs())?;
+                rules.write(f, *sides)
+            }
+            Kind::Constant(value) => write!(f, "{}", value),
+            Kind::Scaled(parts, scale) => {
+                write!(f, "{}", sign)?;
+                match parts.as_slice() {
+                    // A single part only needs brackets if its sign would
+                    // be mistaken for the whole group's.
+                    [part] if !part.is_negative() => write!(f, "{}", part)?,
+                    _ => {
+                        write!(f, "(")?;
+                        write_sum(f, parts)?;
+                        write!(f, ")")?;
+                    }
+                }
+                let op = match scale {
+                    Scale::Mul(_) => '*',
+                    Scale::Div(_) => '/',
+                };
+                write!(f, "{}{}", op, scale.factor().abs())
+            }
+            Kind::Variable { name, .. } => write!(f, "{}${}", sign, name),
+        }
     }
 }
 
-impl fmt::Debug for OutcomePart {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            OutcomePart::Dice(sides, scores) => {
-                if *sides < 0 {
-                    write!(f, "-")?;
-                }
-                let scores = scores.iter().map(|i| format!("{}", i));
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-                write!(f, "[")?;
-                util::write_string_sum(f, scores)?;
-                write!(f, "]")
-            }
+    #[test]
+    fn empty_rules_are_plain_dice() {
+        let dice = Modifier::from_ruled_dice(2, 6, DiceRules::default());
+
+        assert_eq!(dice, Modifier::from_dice(2, 6));
+        assert_eq!(dice.rules(), None);
+        assert_eq!(dice.to_string().parse::<Modifier>().unwrap(), dice);
+    }
+
+    #[test]
+    fn built_round_trip() {
+        let scaled = |parts, scale| Modifier::from_scaled(parts, scale);
 
-            OutcomePart::Modifier(m) => write!(f, "{}", m),
+        for modifier in &[
+            Modifier::from_dice(3, -8),
+            Modifier::from_constant(-4),
+            -Modifier::from_variable("prof_2"),
+            scaled(vec![Modifier::from_dice(1, 6)], Scale::div(-2)),
+            scaled(vec![-Modifier::from_dice(1, 6)], Scale::Mul(3)),
+            scaled(
+                vec![scaled(vec![Modifier::from_constant(7)], Scale::div(2))],
+                Scale::Mul(-2),
+            ),
+        ] {
+            let display = modifier.to_string();
+            assert_eq!(
+                &display.parse::<Modifier>().unwrap(),
+                modifier,
+                "{}",
+                display
+            );
         }
     }
+
+    #[test]
+    #[should_panic]
+    fn from_dice_zero() {
+        Modifier::from_dice(0, 6);
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_variable_invalid() {
+        Modifier::from_variable("s tr");
+    }
+
+    #[test]
+    #[should_panic]
+    fn from_scaled_empty() {
+        Modifier::from_scaled(vec![], Scale::Mul(2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn div_zero() {
+        Scale::div(0);
+    }
 }
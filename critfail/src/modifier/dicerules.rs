@@ -0,0 +1,584 @@
+use rand::Rng;
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::{DieRoll, Distribution, Score, Sides};
+
+/// The most times a single die can explode, so that a die that always
+/// explodes (eg. `1d6!>1`) can't keep rolling forever.
+pub(crate) const MAX_EXPLOSIONS: u32 = 100;
+
+/// Combinations of dice that are less likely than this are ignored
+/// when calculating distributions, so that exploding dice don't produce
+/// an unmanageable number of combinations.
+const NEGLIGIBLE: f64 = 1e-15;
+
+/// Extra rules that change how a set of dice is rolled.
+#[derive(PartialEq, Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiceRules {
+    pub(crate) keep: Option<KeepRule>,
+    /// Dice that roll this value or higher are rolled again and added.
+    pub(crate) explode: Option<Score>,
+    pub(crate) reroll: Option<Reroll>,
+    /// Dice that roll lower than this value count as this value.
+    pub(crate) min: Option<Score>,
+}
+
+/// A rule for rerolling dice that roll too low.
+#[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Reroll {
+    /// Dice that roll this value or lower are rerolled.
+    pub(crate) threshold: Score,
+    /// Whether to keep rerolling until the die rolls higher than
+    /// `threshold` (`rr`), instead of only rerolling once (`r`).
+    pub(crate) recursive: bool,
+}
+
+/// Which dice in a set count towards the score.
+#[derive(PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KeepRule {
+    /// Keep the highest dice (`4d6kh3`).
+    KeepHighest(u32),
+    /// Keep the lowest dice (`2d20kl1`).
+    KeepLowest(u32),
+    /// Drop the highest dice (`4d6dh1`).
+    DropHighest(u32),
+    /// Drop the lowest dice (`4d6dl1`).
+    DropLowest(u32),
+}
+
+impl Reroll {
+    /// Get the value at or below which dice are rerolled.
+    pub fn threshold(&self) -> Score {
+        self.threshold
+    }
+
+    /// Whether dice keep being rerolled until they roll higher than the
+    /// threshold (`rr`), instead of only being rerolled once (`r`).
+    pub fn recursive(&self) -> bool {
+        self.recursive
+    }
+}
+
+impl KeepRule {
+    /// Whether this rule keeps at least one of `num` dice, and doesn't
+    /// keep or drop more dice than there are.
+    pub(crate) fn fits(self, num: u32) -> bool {
+        match self {
+            KeepRule::KeepHighest(n) | KeepRule::KeepLowest(n) => (1..=num).contains(&n),
+            KeepRule::DropHighest(n) | KeepRule::DropLowest(n) => (1..num).contains(&n),
+        }
+    }
+
+    /// Get the number of dice that are kept when rolling `num` dice,
+    /// and whether those are the highest dice or the lowest.
+    fn kept(self, num: u32) -> (u32, bool) {
+        match self {
+            KeepRule::KeepHighest(n) => (n.min(num), true),
+            KeepRule::KeepLowest(n) => (n.min(num), false),
+            KeepRule::DropHighest(n) => (num.saturating_sub(n), false),
+            KeepRule::DropLowest(n) => (num.saturating_sub(n), true),
+        }
+    }
+}
+
+impl DiceRules {
+    /// Whether these rules can be given to `num` dice with `sides`
+    /// sides, which the parser checks as it reads them.
+    #[cfg(feature = "serde")]
+    pub(crate) fn fits(&self, num: u32, sides: Sides) -> bool {
+        let sides = sides.abs();
+        self.keep.is_none_or(|keep| keep.fits(num))
+            && self.explode.is_none_or(|threshold| threshold > 1)
+            && self
+                .reroll
+                .is_none_or(|reroll| !reroll.recursive || reroll.threshold < sides)
+            && self.min.is_none_or(|min| min <= sides)
+    }
+
+    /// Get the rule for which dice count towards the score, if any.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, KeepRule, Modifier};
+    ///
+    /// let dice = Modifier::new("4d6kh3").unwrap();
+    ///
+    /// assert_eq!(dice.rules().unwrap().keep(), Some(KeepRule::KeepHighest(3)));
+    /// ```
+    pub fn keep(&self) -> Option<KeepRule> {
+        self.keep
+    }
+
+    /// Get the value at or above which dice explode, if they explode.
+    pub fn explode(&self) -> Option<Score> {
+        self.explode
+    }
+
+    /// Get the rule for rerolling low dice, if any.
+    pub fn reroll(&self) -> Option<Reroll> {
+        self.reroll
+    }
+
+    /// Get the value that lower rolls count as, if any.
+    pub fn min(&self) -> Option<Score> {
+        self.min
+    }
+
+    /// Roll `num` dice with `sides` sides following these rules.
+    pub(crate) fn roll<R: Rng + ?Sized>(
+        &self,
+        num: u32,
+        sides: Sides,
+        rng: &mut R,
+    ) -> Vec<DieRoll> {
+        let mut dice: Vec<DieRoll> = (0..num)
+            .map(|_| self.roll_die(sides.abs(), rng, 0))
+            .collect();
+
+        if let Some(keep) = self.keep {
+            let (kept, highest) = keep.kept(num);
+            let dropped = dice.len() - kept as usize;
+
+            // Indices of the dice, from lowest to highest
+            let mut order: Vec<usize> = (0..dice.len()).collect();
+            order.sort_by_key(|&i| dice[i].total());
+            if !highest {
+                order.reverse();
+            }
+
+            for &i in &order[..dropped] {
+                dice[i].set_dropped();
+            }
+        }
+
+        dice
+    }
+
+    /// Roll a single die, which has already exploded `explosions`
+    /// times.
+    fn roll_die<R: Rng + ?Sized>(&self, sides: Sides, rng: &mut R, explosions: u32) -> DieRoll {
+        let mut die = DieRoll::new(rng.gen_range(1, sides + 1));
+
+        if let Some(reroll) = self.reroll {
+            while die.value() <= reroll.threshold && (reroll.recursive || die.rerolls().is_empty())
+            {
+                die.reroll(rng.gen_range(1, sides + 1));
+            }
+        }
+
+        if let Some(threshold) = self.explode {
+            if die.value() >= threshold && explosions < MAX_EXPLOSIONS {
+                die.set_explosion(self.roll_die(sides, rng, explosions + 1));
+            }
+        }
+
+        if let Some(min) = self.min {
+            die.raise_to(min);
+        }
+
+        die
+    }
+
+    /// Get the highest score `num` dice with `sides` sides can roll
+    /// following these rules, not counting explosions.
+    pub(crate) fn max(&self, num: u32, sides: Sides) -> Score {
+        let kept = match self.keep {
+            Some(keep) => keep.kept(num).0,
+            None => num,
+        };
+
+        self.raise(sides.abs()) * kept as Score
+    }
+
+    /// Write these rules in the notation they are parsed from, eg.
+    /// `kh3` or `!r<2`, for dice with `sides` sides.
+    pub(crate) fn write(&self, f: &mut fmt::Formatter, sides: Sides) -> fmt::Result {
+        match self.keep {
+            Some(KeepRule::KeepHighest(n)) => write!(f, "kh{}", n)?,
+            Some(KeepRule::KeepLowest(n)) => write!(f, "kl{}", n)?,
+            Some(KeepRule::DropHighest(n)) => write!(f, "dh{}", n)?,
+            Some(KeepRule::DropLowest(n)) => write!(f, "dl{}", n)?,
+            None => (),
+        }
+        match self.explode {
+            // Dice explode on their highest value by default
+            Some(threshold) if threshold == sides.abs() => write!(f, "!")?,
+            Some(threshold) => write!(f, "!>{}", threshold)?,
+            None => (),
+        }
+        if let Some(reroll) = self.reroll {
+            let r = if reroll.recursive { "rr" } else { "r" };
+            write!(f, "{}<{}", r, reroll.threshold)?;
+        }
+        if let Some(min) = self.min {
+            write!(f, "min{}", min)?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the value a die counts as when it rolls `value`.
+    fn raise(&self, value: Score) -> Score {
+        match self.min {
+            Some(min) => Score::max(value, min),
+            None => value,
+        }
+    }
+
+    /// Get the distribution of the value of a single die, after it has
+    /// been rerolled.
+    fn face_distribution(&self, sides: Sides) -> Distribution {
+        let die = Distribution::die(sides);
+
+        match self.reroll {
+            Some(reroll) => {
+                // The chance that the first roll gets rerolled
+                let rerolled: f64 = die
+                    .iter()
+                    .filter(|(value, _)| *value <= reroll.threshold)
+                    .map(|(_, p)| p)
+                    .sum();
+
+                Distribution::from_scores(die.iter().map(|(value, p)| {
+                    let p = if reroll.recursive {
+                        if value <= reroll.threshold {
+                            0.0
+                        } else {
+                            p / (1.0 - rerolled)
+                        }
+                    } else if value <= reroll.threshold {
+                        // Low values are only kept if they come up again
+                        // on the reroll
+                        rerolled * p
+                    } else {
+                        p + rerolled * p
+                    };
+                    (value, p)
+                }))
+            }
+            None => die,
+        }
+    }
+
+    /// Get the distribution of a single die (including explosions).
+    fn die_distribution(&self, sides: Sides) -> Distribution {
+        let face = self.face_distribution(sides);
+        let raised = Distribution::from_scores(face.iter().map(|(v, p)| (self.raise(v), p)));
+
+        match self.explode {
+            Some(threshold) => {
+                // Work backwards from a die that can't explode anymore
+                let mut die = raised;
+                for _ in 0..MAX_EXPLOSIONS {
+                    let exploded = &die;
+                    die = Distribution::from_scores(face.iter().flat_map(|(value, p)| {
+                        let raised = self.raise(value);
+                        if value >= threshold {
+                            exploded.iter().map(|(s, q)| (raised + s, p * q)).collect()
+                        } else {
+                            vec![(raised, p)]
+                        }
+                    }));
+                }
+                die
+            }
+            None => raised,
+        }
+    }
+
+    /// Get the distribution of the score of `num` dice with `sides`
+    /// sides rolled following these rules.
+    pub(crate) fn distribution(&self, num: u32, sides: Sides) -> Distribution {
+        let die = self.die_distribution(sides.abs());
+
+        let dist = match self.keep {
+            Some(keep) => {
+                let (kept, highest) = keep.kept(num);
+                keep_distribution(&die, num, kept, highest)
+            }
+            None => die.repeat(num),
+        };
+
+        if sides < 0 {
+            dist.negate()
+        } else {
+            dist
+        }
+    }
+}
+
+/// Get the distribution of the sum of the `kept` highest (or lowest)
+/// values out of `num` rolls of `die`.
+fn keep_distribution(die: &Distribution, num: u32, kept: u32, highest: bool) -> Distribution {
+    let (num, kept) = (num as usize, kept as usize);
+    if kept == 0 {
+        return Distribution::constant(0);
+    }
+
+    // Go through the faces starting with the ones that are kept first.
+    let mut faces: Vec<(Score, f64)> = die.iter().collect();
+    if highest {
+        faces.reverse();
+    }
+
+    // `states[c]` holds the probability of each kept sum, given that
+    // `c` of the dice rolled one of the faces seen so far.
+    let mut states: Vec<BTreeMap<Score, f64>> = vec![BTreeMap::new(); kept];
+    states[0].insert(0, 1.0);
+    let mut remaining = 1.0;
+    let mut result = Vec::new();
+
+    for (face, p) in faces {
+        // The chance of a die rolling one of the faces after this one
+        remaining = f64::max(remaining - p, 0.0);
+        let mut next: Vec<BTreeMap<Score, f64>> = vec![BTreeMap::new(); kept];
+
+        for (c, sums) in states.iter().enumerate() {
+            for (&sum, &weight) in sums {
+                // `j` more dice rolled this face
+                let mut ways = 1.0;
+                for j in 0..=(num - c) {
+                    if j > 0 {
+                        ways = ways * (num - c - j + 1) as f64 / j as f64;
+                    }
+                    let weight = weight * ways * p.powi(j as i32);
+                    if weight < NEGLIGIBLE {
+                        continue;
+                    }
+
+                    let sum = sum + face * (usize::min(c + j, kept) - c) as Score;
+                    if c + j >= kept {
+                        // All the kept dice have been found, so the
+                        // rest of the dice just need to roll lower.
+                        let rest = remaining.powi((num - c - j) as i32);
+                        result.push((sum, weight * rest));
+                    } else {
+                        *next[c + j].entry(sum).or_insert(0.0) += weight;
+                    }
+                }
+            }
+        }
+
+        states = next;
+    }
+
+    Distribution::from_scores(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn keep(rule: KeepRule) -> DiceRules {
+        DiceRules {
+            keep: Some(rule),
+            ..Default::default()
+        }
+    }
+
+    fn explode_at(threshold: Score) -> DiceRules {
+        DiceRules {
+            explode: Some(threshold),
+            ..Default::default()
+        }
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn keep_highest() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..20 {
+            let dice = keep(KeepRule::KeepHighest(3)).roll(4, 6, &mut rng);
+            let dropped: Vec<_> = dice.iter().filter(|d| d.is_dropped()).collect();
+            let lowest = dice.iter().map(|d| d.value()).min().unwrap();
+
+            assert_eq!(dice.len(), 4);
+            assert_eq!(dropped.len(), 1);
+            assert_eq!(dropped[0].value(), lowest);
+        }
+    }
+
+    #[test]
+    fn drop_highest() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..20 {
+            let dice = keep(KeepRule::DropHighest(2)).roll(5, 8, &mut rng);
+            let highest = dice.iter().map(|d| d.value()).max().unwrap();
+
+            assert_eq!(dice.iter().filter(|d| d.is_dropped()).count(), 2);
+            assert!(dice.iter().any(|d| d.is_dropped() && d.value() == highest));
+        }
+    }
+
+    #[test]
+    fn keep_more_than_rolled() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let dice = keep(KeepRule::KeepLowest(4)).roll(2, 6, &mut rng);
+        assert!(dice.iter().all(|d| !d.is_dropped()));
+    }
+
+    #[test]
+    fn keep_distribution() {
+        let dist = keep(KeepRule::KeepHighest(3)).distribution(4, 6);
+        assert_eq!(dist.min(), 3);
+        assert_eq!(dist.max(), 18);
+        assert_close(dist.mean(), 15869.0 / 1296.0);
+        assert_close(dist.probability(18), 21.0 / 1296.0);
+
+        // Keeping the highest d20 is the same as rolling with advantage
+        let adv = keep(KeepRule::KeepHighest(1)).distribution(2, 20);
+        assert_close(adv.mean(), 13.825);
+        assert_eq!(
+            keep(KeepRule::DropHighest(1)).distribution(2, 20),
+            keep(KeepRule::KeepLowest(1)).distribution(2, 20),
+        );
+    }
+
+    #[test]
+    fn explode_rolls() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let dice = explode_at(5).roll(200, 6, &mut rng);
+
+        assert!(dice.iter().any(|d| d.explosion().is_some()));
+        for die in dice {
+            match die.explosion() {
+                Some(explosion) => {
+                    assert!(die.value() >= 5);
+                    assert_eq!(die.total(), die.value() + explosion.total());
+                }
+                None => {
+                    assert!(die.value() < 5);
+                    assert_eq!(die.total(), die.value());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn explosion_cap() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let die = explode_at(1).roll(1, 6, &mut rng).remove(0);
+
+        let mut depth = 0;
+        let mut d = &die;
+        while let Some(e) = d.explosion() {
+            depth += 1;
+            d = e;
+        }
+        assert_eq!(depth, MAX_EXPLOSIONS);
+    }
+
+    #[test]
+    fn explode_distribution() {
+        let dist = explode_at(6).distribution(1, 6);
+        assert_eq!(dist.min(), 1);
+        assert_close(dist.probability(5), 1.0 / 6.0);
+        assert_close(dist.probability(6), 0.0);
+        assert_close(dist.probability(7), 1.0 / 36.0);
+        // The mean of an exploding die is sides * (sides + 1) / (2 * (sides - 1))
+        assert_close(dist.mean(), 4.2);
+        assert_close(dist.iter().map(|(_, p)| p).sum(), 1.0);
+    }
+
+    #[test]
+    fn explode_keep_distribution() {
+        let rules = DiceRules {
+            keep: Some(KeepRule::KeepHighest(1)),
+            explode: Some(4),
+            ..Default::default()
+        };
+        let dist = rules.distribution(2, 4);
+        assert_eq!(dist.min(), 1);
+        assert_close(dist.probability(1), 1.0 / 16.0);
+        assert_close(dist.probability(4), 0.0);
+        assert_close(dist.iter().map(|(_, p)| p).sum(), 1.0);
+    }
+
+    #[test]
+    fn reroll_once() {
+        let rules = DiceRules {
+            reroll: Some(Reroll {
+                threshold: 2,
+                recursive: false,
+            }),
+            ..Default::default()
+        };
+
+        let mut rng = StdRng::seed_from_u64(6);
+        let dice = rules.roll(200, 6, &mut rng);
+        assert!(dice.iter().any(|d| d.value() <= 2));
+        for die in &dice {
+            match die.rerolls() {
+                [] => assert!(die.value() > 2),
+                [reroll] => assert!(*reroll <= 2),
+                _ => panic!("rerolled more than once"),
+            }
+        }
+
+        // Great Weapon Fighting
+        let dist = rules.distribution(1, 6);
+        assert_close(dist.probability(1), 1.0 / 18.0);
+        assert_close(dist.probability(6), 2.0 / 9.0);
+        assert_close(dist.mean(), 25.0 / 6.0);
+    }
+
+    #[test]
+    fn reroll_recursive() {
+        let rules = DiceRules {
+            reroll: Some(Reroll {
+                threshold: 3,
+                recursive: true,
+            }),
+            ..Default::default()
+        };
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let dice = rules.roll(200, 6, &mut rng);
+        assert!(dice.iter().any(|d| d.rerolls().len() > 1));
+        for die in &dice {
+            assert!(die.value() > 3);
+            assert!(die.rerolls().iter().all(|r| *r <= 3));
+        }
+
+        let dist = rules.distribution(1, 6);
+        assert_eq!(dist.min(), 4);
+        assert_close(dist.probability(4), 1.0 / 3.0);
+    }
+
+    #[test]
+    fn min() {
+        let rules = DiceRules {
+            min: Some(2),
+            ..Default::default()
+        };
+
+        let mut rng = StdRng::seed_from_u64(8);
+        let dice = rules.roll(100, 6, &mut rng);
+        assert!(dice.iter().any(|d| d.natural() == 1));
+        for die in &dice {
+            assert!(die.value() >= 2);
+            assert_eq!(die.value(), Score::max(die.natural(), 2));
+        }
+
+        // Elemental Adept
+        let dist = rules.distribution(1, 6);
+        assert_eq!(dist.min(), 2);
+        assert_close(dist.probability(2), 2.0 / 6.0);
+        assert_close(dist.mean(), 3.0 + 2.0 / 3.0);
+    }
+
+    #[test]
+    fn negative_distribution() {
+        let dist = keep(KeepRule::DropLowest(1)).distribution(2, -4);
+        assert_eq!(dist.min(), -4);
+        assert_eq!(dist.max(), -1);
+        assert_close(dist.probability(-4), 7.0 / 16.0);
+    }
+}
@@ -0,0 +1,361 @@
+use crate::{util, DamageType, Scale, Score, Sides};
+use std::fmt;
+
+/// Internally used wrapper struct for a collection of OutcomeParts.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub(crate) struct ModifiersOutcome {
+    // OPTIMIZE: Cache sum in this struct
+    scores: Vec<OutcomePart>,
+}
+
+impl ModifiersOutcome {
+    pub fn score(&self) -> Score {
+        self.scores.iter().map(|s| s.score()).sum()
+    }
+
+    pub(crate) fn iter(&self) -> std::slice::Iter<'_, OutcomePart> {
+        self.scores.iter()
+    }
+
+    /// Get the total score of each damage type, in the order the types
+    /// first appear.
+    pub(crate) fn scores_by_type(&self) -> Vec<(Option<DamageType>, Score)> {
+        let mut result: Vec<(Option<DamageType>, Score)> = Vec::new();
+
+        for part in &self.scores {
+            let t = part.damage_type();
+            match result.iter_mut().find(|(other, _)| *other == t) {
+                Some((_, score)) => *score += part.score(),
+                None => result.push((t, part.score())),
+            }
+        }
+
+        result
+    }
+}
+
+impl IntoIterator for ModifiersOutcome {
+    type Item = OutcomePart;
+    type IntoIter = std::vec::IntoIter<OutcomePart>;
+
+    fn into_iter(self) -> std::vec::IntoIter<OutcomePart> {
+        self.scores.into_iter()
+    }
+}
+
+impl From<Vec<OutcomePart>> for ModifiersOutcome {
+    fn from(scores: Vec<OutcomePart>) -> Self {
+        Self { scores }
+    }
+}
+
+/// Enum representing the different kinds of values that can be returned
+/// for damage or the modifier on a check.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OutcomePart {
+    /// The result of rolling a set of dice.
+    ///
+    /// For example, If I rolled 3d6 and got a 4, a 2, and a 1, that
+    /// would be `Dice(6, vec![4,2,1])`.
+    Dice(Sides, Vec<Score>),
+    /// The result of rolling a set of dice with extra rules, like
+    /// keeping only the highest few dice or exploding dice.
+    ///
+    /// Unlike `Dice`, each die records how the rules applied to it.
+    RuledDice(Sides, Vec<DieRoll>),
+    /// A constant modifier value.
+    Modifier(Score),
+    /// Another part that deals a specific type of damage.
+    Typed(Box<OutcomePart>, DamageType),
+    /// A group of parts whose total is multiplied or divided, eg. the
+    /// result of rolling `(2d6+3)*2` or `8d6/2`.
+    Scaled(Vec<OutcomePart>, Scale),
+}
+
+/// A single die in an `OutcomePart::RuledDice`.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DieRoll {
+    value: Score,
+    raised_from: Option<Score>,
+    rerolls: Vec<Score>,
+    explosion: Option<Box<DieRoll>>,
+    dropped: bool,
+}
+
+impl DieRoll {
+    pub(crate) fn new(value: Score) -> Self {
+        Self {
+            value,
+            raised_from: None,
+            rerolls: Vec::new(),
+            explosion: None,
+            dropped: false,
+        }
+    }
+
+    /// Replace the value of this die with `value`, keeping track of
+    /// the value that was discarded.
+    pub(crate) fn reroll(&mut self, value: Score) {
+        self.rerolls.push(self.value);
+        self.value = value;
+    }
+
+    /// Raise the value of this die to `min` if it rolled lower.
+    pub(crate) fn raise_to(&mut self, min: Score) {
+        if self.value < min {
+            self.raised_from = Some(self.value);
+            self.value = min;
+        }
+    }
+
+    pub(crate) fn set_dropped(&mut self) {
+        self.dropped = true;
+    }
+
+    pub(crate) fn set_explosion(&mut self, explosion: DieRoll) {
+        self.explosion = Some(Box::new(explosion));
+    }
+
+    /// The value of this die, not counting any explosions.
+    ///
+    /// If the die was rerolled, this is the value that was kept. If the
+    /// die was raised to a minimum value, this is the minimum.
+    pub fn value(&self) -> Score {
+        self.value
+    }
+
+    /// The value that was actually rolled on this die, before it was
+    /// raised to a minimum value.
+    pub fn natural(&self) -> Score {
+        self.raised_from.unwrap_or(self.value)
+    }
+
+    /// The values that were discarded because the die was rerolled, in
+    /// the order they were rolled.
+    pub fn rerolls(&self) -> &[Score] {
+        &self.rerolls
+    }
+
+    /// The extra die that was rolled because this die exploded, if it
+    /// did.
+    pub fn explosion(&self) -> Option<&DieRoll> {
+        self.explosion.as_deref()
+    }
+
+    /// The value of this die plus the values of all the dice rolled
+    /// when it exploded.
+    pub fn total(&self) -> Score {
+        self.value + self.explosion.as_ref().map(|e| e.total()).unwrap_or(0)
+    }
+
+    /// Whether this die was dropped, eg. for being one of the lowest
+    /// dice when rolling `4d6kh3`. Dropped dice don't count towards
+    /// the score.
+    pub fn is_dropped(&self) -> bool {
+        self.dropped
+    }
+
+    /// The amount this die adds to the score.
+    fn score(&self) -> Score {
+        if self.dropped {
+            0
+        } else {
+            self.total()
+        }
+    }
+}
+
+impl OutcomePart {
+    /// Get the score of this `OutcomePart`.
+    ///
+    /// Either the sum of the die rolls, or the value of the modifier.
+    pub fn score(&self) -> Score {
+        match self {
+            Self::Dice(sides, d) => {
+                let sum: Score = d.iter().sum();
+                if *sides < 0 {
+                    -sum
+                } else {
+                    sum
+                }
+            }
+            Self::RuledDice(sides, d) => {
+                let sum: Score = d.iter().map(|d| d.score()).sum();
+                if *sides < 0 {
+                    -sum
+                } else {
+                    sum
+                }
+            }
+            Self::Modifier(m) => *m,
+            Self::Typed(part, _) => part.score(),
+            Self::Scaled(parts, scale) => scale.apply(parts.iter().map(|p| p.score()).sum()),
+        }
+    }
+
+    /// Get the number of sides of the dice in this `OutcomePart`, which
+    /// is negative if the dice are subtracted. Modifiers and scaled
+    /// groups don't have any sides.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Damage};
+    ///
+    /// let outcome = Damage::new("1d8-1d4+3 fire").unwrap().roll();
+    /// let sides: Vec<_> = outcome.parts().map(|p| p.sides()).collect();
+    ///
+    /// assert_eq!(sides, vec![Some(8), Some(-4), None]);
+    /// ```
+    pub fn sides(&self) -> Option<Sides> {
+        match self {
+            Self::Dice(sides, _) | Self::RuledDice(sides, _) => Some(*sides),
+            Self::Typed(part, _) => part.sides(),
+            Self::Modifier(_) | Self::Scaled(..) => None,
+        }
+    }
+
+    /// Get the values in this `OutcomePart`: the value of each die in
+    /// the order they were rolled, or the value of a modifier.
+    ///
+    /// For dice with extra rules, this is the `DieRoll::value()` of
+    /// each die, including dropped dice. The parts of a scaled group
+    /// aren't included, since they can have different sides.
+    ///
+    /// ```
+    /// use critfail::DamageOutcomeBuilder;
+    ///
+    /// let outcome = DamageOutcomeBuilder::new()
+    ///     .dice(6, vec![4, 1])
+    ///     .modifier(3)
+    ///     .build();
+    /// let values: Vec<_> = outcome.parts().map(|p| p.values()).collect();
+    ///
+    /// assert_eq!(values, vec![vec![4, 1], vec![3]]);
+    /// ```
+    pub fn values(&self) -> Vec<Score> {
+        match self {
+            Self::Dice(_, rolls) => rolls.clone(),
+            Self::RuledDice(_, dice) => dice.iter().map(|d| d.value()).collect(),
+            Self::Modifier(m) => vec![*m],
+            Self::Typed(part, _) => part.values(),
+            Self::Scaled(..) => vec![],
+        }
+    }
+
+    /// Get the damage type of this `OutcomePart`, if it has one.
+    pub fn damage_type(&self) -> Option<DamageType> {
+        match self {
+            Self::Typed(_, t) => Some(*t),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ModifiersOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.score())
+    }
+}
+
+impl fmt::Debug for ModifiersOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Only write the damage type after the last part of each run of
+        // parts with the same type.
+        let scores = self.scores.iter().enumerate().map(|(i, s)| match s {
+            OutcomePart::Typed(part, t)
+                if self.scores.get(i + 1).and_then(|n| n.damage_type()) == Some(*t) =>
+            {
+                format!("{:?}", part)
+            }
+            _ => format!("{:?}", s),
+        });
+        util::write_string_sum(f, scores)
+    }
+}
+
+impl fmt::Display for OutcomePart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.score())
+    }
+}
+
+impl fmt::Debug for OutcomePart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutcomePart::Dice(sides, scores) => {
+                if *sides < 0 {
+                    write!(f, "-")?;
+                }
+                let scores = scores.iter().map(|i| format!("{}", i));
+
+                write!(f, "[")?;
+                util::write_string_sum(f, scores)?;
+                write!(f, "]")
+            }
+
+            OutcomePart::RuledDice(sides, dice) => {
+                if *sides < 0 {
+                    write!(f, "-")?;
+                }
+                let dice = dice.iter().map(|d| format!("{:?}", d));
+
+                write!(f, "[")?;
+                util::write_string_sum(f, dice)?;
+                write!(f, "]")
+            }
+
+            OutcomePart::Modifier(m) => write!(f, "{}", m),
+
+            OutcomePart::Typed(part, t) => write!(f, "{:?} {}", part, t),
+
+            OutcomePart::Scaled(parts, scale) => {
+                let op = match scale {
+                    Scale::Mul(_) => '*',
+                    Scale::Div(_) => '/',
+                };
+                let factor = scale.factor();
+                if factor < 0 {
+                    write!(f, "-")?;
+                }
+
+                // A single part doesn't need parentheses, eg. `[2+5]/2`
+                match parts.as_slice() {
+                    [part] if part.damage_type().is_none() => write!(f, "{:?}", part)?,
+                    _ => {
+                        write!(f, "(")?;
+                        util::write_string_sum(f, parts.iter().map(|p| format!("{:?}", p)))?;
+                        write!(f, ")")?;
+                    }
+                }
+                write!(f, "{}{}", op, factor.abs())
+            }
+        }
+    }
+}
+
+impl fmt::Debug for DieRoll {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.dropped {
+            write!(f, "(")?;
+        }
+
+        for reroll in &self.rerolls {
+            write!(f, "{}→", reroll)?;
+        }
+        if let Some(raised_from) = self.raised_from {
+            write!(f, "{}→", raised_from)?;
+        }
+        write!(f, "{}", self.value)?;
+        if let Some(explosion) = &self.explosion {
+            write!(f, "!+{:?}", explosion)?;
+        }
+
+        if self.dropped {
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,251 @@
+use std::str::FromStr;
+
+use crate::modifier::Modifier;
+use crate::parse::{self, Atom, DamageExpr};
+use crate::ParseErrorKind::*;
+use crate::{DamageType, ParseError};
+
+impl FromStr for Modifier {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expr = parse::damage(s)?;
+        if expr.crit_rule.is_some() {
+            return Err(ParseError::whole(Unexpected, s));
+        }
+
+        let mut parts = flatten(expr);
+        match parts.len() {
+            0 => Err(ParseError::whole(Empty, s)),
+            1 if parts[0].1.is_none() => Ok(parts.remove(0).0),
+            _ => Err(ParseError::whole(Unexpected, s)),
+        }
+    }
+}
+
+/// Get the terms of `expr` as a flat list of modifiers, each with the
+/// damage type that was written after it (or after the group it is in).
+/// Groups are flattened into their parts.
+pub(crate) fn flatten(expr: DamageExpr) -> Vec<(Modifier, Option<DamageType>)> {
+    let mut parts = vec![];
+    flatten_into(expr, false, &mut parts);
+    parts
+}
+
+/// Add the terms of `expr` to `parts`, negating them if `negative` is
+/// set.
+fn flatten_into(expr: DamageExpr, negative: bool, parts: &mut Vec<(Modifier, Option<DamageType>)>) {
+    for term in expr.terms {
+        flatten_atom(
+            term.atom,
+            negative != term.negative,
+            term.damage_type,
+            parts,
+        );
+    }
+}
+
+/// Add `atom` to `parts`, negating it if `negative` is set.
+fn flatten_atom(
+    atom: Atom,
+    negative: bool,
+    damage_type: Option<DamageType>,
+    parts: &mut Vec<(Modifier, Option<DamageType>)>,
+) {
+    let sign = |modifier: Modifier| if negative { -modifier } else { modifier };
+
+    match atom {
+        Atom::Dice(num, sides, rules) => {
+            let dice = Modifier::from_ruled_dice(num, sides, rules.unwrap_or_default());
+            parts.push((sign(dice), damage_type))
+        }
+        Atom::Number(n) => parts.push((sign(Modifier::from_constant(n)), damage_type)),
+        Atom::Variable(name) => parts.push((sign(Modifier::from_variable(&name)), damage_type)),
+        Atom::Group(inner) => {
+            let start = parts.len();
+            flatten_into(inner, negative, parts);
+
+            // A type on a group applies to everything in it
+            if let Some(t) = damage_type {
+                for (_, damage_type) in parts[start..].iter_mut() {
+                    damage_type.get_or_insert(t);
+                }
+            }
+        }
+        Atom::Scaled(atom, scale) => {
+            // A scaled group stays together, and its sign goes on the
+            // outermost scale so that it is rounded the right way.
+            let mut inner = vec![];
+            flatten_atom(*atom, false, None, &mut inner);
+            let scale = if negative { scale.negated() } else { scale };
+
+            let inner = inner.into_iter().map(|(part, _)| part).collect();
+            parts.push((Modifier::from_scaled(inner, scale), damage_type));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DiceRules, KeepRule, Reroll};
+
+    macro_rules! test_modifier {
+        ($string:expr, $modifier:expr) => {
+            let m = $string.parse::<Modifier>().unwrap();
+            assert_eq!(m, $modifier);
+        };
+    }
+
+    #[test]
+    fn dice() {
+        test_modifier!("2d8", Modifier::from_dice(2, 8));
+        test_modifier!("1d12", Modifier::from_dice(1, 12));
+        test_modifier!("3d6", Modifier::from_dice(3, 6));
+        test_modifier!("421d314159", Modifier::from_dice(421, 314_159));
+    }
+
+    #[test]
+    fn negative_dice() {
+        test_modifier!("-2d8", Modifier::from_dice(2, -8));
+        test_modifier!("-1d12", Modifier::from_dice(1, -12));
+        test_modifier!("-3d6", Modifier::from_dice(3, -6));
+        test_modifier!("-421d314159", Modifier::from_dice(421, -314_159));
+    }
+
+    #[test]
+    fn constant() {
+        test_modifier!("4", Modifier::from_constant(4));
+        test_modifier!("-3", Modifier::from_constant(-3));
+        test_modifier!("26", Modifier::from_constant(26));
+        test_modifier!("-129", Modifier::from_constant(-129));
+    }
+
+    #[test]
+    fn keep() {
+        let rules = |keep| DiceRules {
+            keep: Some(keep),
+            ..Default::default()
+        };
+
+        test_modifier!(
+            "4d6kh3",
+            Modifier::from_ruled_dice(4, 6, rules(KeepRule::KeepHighest(3)))
+        );
+        test_modifier!(
+            "2d20kl1",
+            Modifier::from_ruled_dice(2, 20, rules(KeepRule::KeepLowest(1)))
+        );
+        test_modifier!(
+            "4d6dl1",
+            Modifier::from_ruled_dice(4, 6, rules(KeepRule::DropLowest(1)))
+        );
+        test_modifier!(
+            "-3d8dh",
+            Modifier::from_ruled_dice(3, -8, rules(KeepRule::DropHighest(1)))
+        );
+    }
+
+    #[test]
+    fn explode() {
+        let rules = |explode| DiceRules {
+            explode: Some(explode),
+            ..Default::default()
+        };
+
+        test_modifier!("1d6!", Modifier::from_ruled_dice(1, 6, rules(6)));
+        test_modifier!("2d10!>9", Modifier::from_ruled_dice(2, 10, rules(9)));
+        test_modifier!("-1d4!", Modifier::from_ruled_dice(1, -4, rules(4)));
+        test_modifier!(
+            "4d6!kh3",
+            Modifier::from_ruled_dice(
+                4,
+                6,
+                DiceRules {
+                    keep: Some(KeepRule::KeepHighest(3)),
+                    explode: Some(6),
+                    ..Default::default()
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn reroll() {
+        let rules = |threshold, recursive| DiceRules {
+            reroll: Some(Reroll {
+                threshold,
+                recursive,
+            }),
+            ..Default::default()
+        };
+
+        test_modifier!("2d6r<2", Modifier::from_ruled_dice(2, 6, rules(2, false)));
+        test_modifier!("1d8rr<1", Modifier::from_ruled_dice(1, 8, rules(1, true)));
+        test_modifier!("1d4r<4", Modifier::from_ruled_dice(1, 4, rules(4, false)));
+    }
+
+    #[test]
+    fn min() {
+        let rules = |min| DiceRules {
+            min: Some(min),
+            ..Default::default()
+        };
+
+        test_modifier!("8d6min2", Modifier::from_ruled_dice(8, 6, rules(2)));
+        test_modifier!("-1d4min3", Modifier::from_ruled_dice(1, -4, rules(3)));
+    }
+
+    #[test]
+    fn implicit_count() {
+        test_modifier!("d20", Modifier::from_dice(1, 20));
+        test_modifier!("-d4", Modifier::from_dice(1, -4));
+    }
+
+    #[test]
+    fn upper_case() {
+        test_modifier!("2D6", Modifier::from_dice(2, 6));
+        test_modifier!(
+            "4D6KH3",
+            Modifier::from_ruled_dice(
+                4,
+                6,
+                DiceRules {
+                    keep: Some(KeepRule::KeepHighest(3)),
+                    ..Default::default()
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!("2d6 fire".parse::<Modifier>().is_err());
+        assert!("2d6 fire cold".parse::<Modifier>().is_err());
+        assert!("2d6 crit max".parse::<Modifier>().is_err());
+        assert!("8d6min".parse::<Modifier>().is_err());
+        assert!("8d6min2min3".parse::<Modifier>().is_err());
+        assert!("1d6rr<6".parse::<Modifier>().is_err());
+        assert!("1d6r<1r<2".parse::<Modifier>().is_err());
+        assert!("1d6r2".parse::<Modifier>().is_err());
+        assert!("1d1!".parse::<Modifier>().is_err());
+        assert!("1d6!>1".parse::<Modifier>().is_err());
+        assert!("1d6!!".parse::<Modifier>().is_err());
+        assert!("1d6!>".parse::<Modifier>().is_err());
+        assert!("4d6kh3kl1".parse::<Modifier>().is_err());
+        assert!("4d6k3".parse::<Modifier>().is_err());
+        assert!("4d6kh3x".parse::<Modifier>().is_err());
+        assert!("1d6kh0".parse::<Modifier>().is_err());
+        assert!("1d6kh7".parse::<Modifier>().is_err());
+        assert!("2d20kl3".parse::<Modifier>().is_err());
+        assert!("4d6dl4".parse::<Modifier>().is_err());
+        assert!("4d6dh0".parse::<Modifier>().is_err());
+        assert!("1d6min7".parse::<Modifier>().is_err());
+        assert!("2q4".parse::<Modifier>().is_err());
+        assert!("r+3".parse::<Modifier>().is_err());
+        assert!("d-3".parse::<Modifier>().is_err());
+        assert!("d".parse::<Modifier>().is_err());
+        assert!("".parse::<Modifier>().is_err());
+        assert!("2d6+3".parse::<Modifier>().is_err());
+    }
+}
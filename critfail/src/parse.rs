@@ -0,0 +1,809 @@
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::check::{CRIT_RANGES, D20_MINIMUMS};
+use crate::AdvState::*;
+use crate::ParseErrorKind::*;
+use crate::{Ability, CritRule, DamageType, ParseError, SaveEffect, Scale, Score, Sides};
+use crate::{DiceRules, KeepRule, Reroll};
+
+pub(crate) use ast::*;
+use token::{tokenize, Token, TokenKind};
+
+mod ast;
+mod token;
+
+/// Words that can come after a damage expression without being a
+/// damage type.
+const FOLLOW_WORDS: [&str; 6] = ["vs", "min", "c", "crit", "half", "negates"];
+
+/// What can start a term of a sum.
+const TERM: &[&str] = &["a number", "dice", "a variable", "("];
+
+/// Parse a roll expression of any kind.
+pub(crate) fn roll(s: &str) -> Result<Expr, ParseError> {
+    Parser::new(s)?.all(Parser::roll)
+}
+
+/// Parse a check expression, eg. `a+5 vs 15`.
+pub(crate) fn check(s: &str) -> Result<CheckExpr, ParseError> {
+    Parser::new(s)?.all(Parser::check)
+}
+
+/// Parse a damage expression, eg. `2d6+3 fire crit max`. The
+/// expression can be empty.
+pub(crate) fn damage(s: &str) -> Result<DamageExpr, ParseError> {
+    Parser::new(s)?.all(|p| p.damage_with_rule(false))
+}
+
+/// Parse an attack expression, eg. `r+5?1d8+3`.
+pub(crate) fn attack(s: &str) -> Result<AttackExpr, ParseError> {
+    Parser::new(s)?.all(Parser::attack)
+}
+
+/// Parse a save expression, eg. `save DC15 dex +3 : 8d6 half`.
+pub(crate) fn save(s: &str) -> Result<SaveExpr, ParseError> {
+    Parser::new(s)?.all(Parser::save)
+}
+
+/// A recursive descent parser for roll expressions.
+///
+/// The grammar is roughly:
+///
+/// ```text
+/// roll   := NUMBER 'x' roll | save | attack | check | damage
+/// save   := 'save' 'dc' NUMBER [ability] [check] ':' damage [crit] ['half' | 'negates']
+/// attack := check '?' damage [crit] [target]
+/// check  := ('r' | 'a' | 'd' | <nothing>) ['+'] [damage] ['c' NUMBER] ['min' NUMBER] [target]
+/// target := 'vs' ['ac' | 'dc'] NUMBER
+/// crit   := 'crit' ('dice' | 'max' | 'total' | 'none' | NUMBER (',' NUMBER)*)
+/// damage := term (('+' | '-') term)*
+/// term   := ['-'] atom (('*' | '/') NUMBER)* [damage type]
+/// atom   := dice | NUMBER | VARIABLE | '(' damage ')'
+/// dice   := [NUMBER] 'd' NUMBER rules
+/// ```
+///
+/// Words are case insensitive, and whitespace is allowed between most
+/// tokens. The tokens within dice (`4d6kh3`) have to be written
+/// together, and `min`, `vs`, `crit` and damage types have to be
+/// separated from what comes before them.
+struct Parser<'a> {
+    s: &'a str,
+    tokens: Vec<Token>,
+    /// The index of the next token.
+    pos: usize,
+    /// The end of the last thing that was parsed.
+    end: usize,
+    /// Whether the damage being parsed is the modifier of a check,
+    /// which can't have damage types.
+    in_check: bool,
+}
+
+impl<'a> Parser<'a> {
+    fn new(s: &'a str) -> Result<Self, ParseError> {
+        Ok(Parser {
+            s,
+            tokens: tokenize(s)?,
+            pos: 0,
+            end: 0,
+            in_check: false,
+        })
+    }
+
+    /// Run `parse`, and make sure it used up the whole expression.
+    fn all<T, F>(mut self, parse: F) -> Result<T, ParseError>
+    where
+        F: FnOnce(&mut Self) -> Result<T, ParseError>,
+    {
+        let result = parse(&mut self)?;
+        match self.peek() {
+            None => Ok(result),
+            Some(t) => Err(ParseError::new(UnknownToken, self.s, t.span.clone())),
+        }
+    }
+
+    /* Grammar rules */
+
+    fn roll(&mut self) -> Result<Expr, ParseError> {
+        if self.peek().is_none() {
+            return Err(ParseError::new(Empty, self.s, self.here()));
+        }
+
+        // Repeated rolls, eg. `3x r+5?1d8+3`
+        if self.at(TokenKind::Number) && self.at_prefix_after("x") {
+            let (n, count) = self.expect_number::<u32>(&["a count"])?;
+            self.eat_prefix("x");
+            if n == 0 {
+                return Err(ParseError::new(InvalidNumber, self.s, count)
+                    .expecting(&["a count of at least 1"]));
+            }
+
+            // Repeated rolls can't be nested
+            let start = self.here().start;
+            let roll = self.roll()?;
+            if let Expr::Repeated(..) = roll {
+                return Err(ParseError::new(Unexpected, self.s, start..self.end));
+            }
+            return Ok(Expr::Repeated(n, Box::new(roll)));
+        }
+
+        if self.is_word("save") {
+            Ok(Expr::Save(self.save()?))
+        } else if self.tokens[self.pos..]
+            .iter()
+            .any(|t| t.kind == TokenKind::Question)
+        {
+            Ok(Expr::Attack(self.attack()?))
+        } else if self.at_check() {
+            Ok(Expr::Check(self.check()?))
+        } else {
+            Ok(Expr::Damage(self.damage_with_rule(true)?))
+        }
+    }
+
+    fn save(&mut self) -> Result<SaveExpr, ParseError> {
+        self.expect_word("save")?;
+        self.expect_word("dc")?;
+        let (dc, _) = self.expect_number(&["a DC"])?;
+
+        // The ability and the modifier of the saving throw are optional
+        let ability = match self.peek() {
+            Some(t) if t.kind == TokenKind::Word && t.space_before => {
+                match self.text(t).to_ascii_lowercase().parse::<Ability>() {
+                    Ok(ability) => {
+                        self.next();
+                        Some(ability)
+                    }
+                    Err(_) => None,
+                }
+            }
+            _ => None,
+        };
+
+        let start = self.here().start;
+        let check = if self.at(TokenKind::Colon) {
+            CheckExpr::default()
+        } else {
+            self.check()?
+        };
+        if check.target.is_some() {
+            return Err(ParseError::new(Unexpected, self.s, start..self.end));
+        }
+
+        let colon = self.expect(TokenKind::Colon, &[":"])?;
+        if self.peek().is_none() {
+            return Err(ParseError::new(DanglingOperator, self.s, colon).expecting(&["damage"]));
+        }
+        let damage = self.damage_with_rule(true)?;
+
+        let effect = if self.eat_word("negates").is_some() {
+            SaveEffect::Negates
+        } else {
+            self.eat_word("half");
+            SaveEffect::Half
+        };
+
+        Ok(SaveExpr {
+            dc,
+            ability,
+            check,
+            damage,
+            effect,
+        })
+    }
+
+    fn attack(&mut self) -> Result<AttackExpr, ParseError> {
+        // The check and the damage are separated by exactly one `?`
+        let mut questions = self.tokens[self.pos..]
+            .iter()
+            .filter(|t| t.kind == TokenKind::Question);
+        if let Some(second) = questions.nth(1) {
+            return Err(ParseError::new(
+                MultipleQuestionMarks,
+                self.s,
+                second.span.clone(),
+            ));
+        }
+        if let Some(q) = self.eat(TokenKind::Question) {
+            return Err(ParseError::new(DanglingOperator, self.s, q).expecting(&["a check"]));
+        }
+
+        let mut check = self.check()?;
+        let q = self.expect(TokenKind::Question, &["?"])?;
+        if self.peek().is_none() {
+            return Err(ParseError::new(DanglingOperator, self.s, q).expecting(&["damage"]));
+        }
+        let damage = self.damage_with_rule(true)?;
+
+        // The AC can come at the end of the attack
+        if let Some(target) = self.target()? {
+            check.target = Some(target);
+        }
+
+        Ok(AttackExpr { check, damage })
+    }
+
+    fn check(&mut self) -> Result<CheckExpr, ParseError> {
+        // Figure out the advantage from the first character
+        let adv = match self.peek().map(|t| t.kind) {
+            None => return Err(ParseError::new(Empty, self.s, self.here())),
+            Some(TokenKind::Plus) | Some(TokenKind::Minus) => Neutral,
+            _ if self.eat_prefix("r").is_some() => Neutral,
+            _ if self.eat_prefix("a").is_some() => Advantage,
+            _ if self.eat_prefix("d").is_some() => Disadvantage,
+            _ => {
+                return Err(ParseError::new(UnknownToken, self.s, self.here())
+                    .expecting(&["r", "a", "d", "+", "-"]))
+            }
+        };
+
+        // If the character after the advantage character is a +, skip it.
+        // Only a modifier can follow it, unless it ends the check like
+        // in `r+` or `r+?1d8`.
+        let required = self.eat(TokenKind::Plus).is_some()
+            && match self.peek() {
+                None => false,
+                Some(t) => t.kind != TokenKind::Question && t.kind != TokenKind::Colon,
+            };
+
+        // The rest is the modifier, which can't have damage types
+        let start = self.here().start;
+        self.in_check = true;
+        let modifier = self.damage(required);
+        self.in_check = false;
+        let modifier = modifier?;
+        if modifier.has_types() {
+            return Err(ParseError::new(Unexpected, self.s, start..self.end));
+        }
+
+        let crit = match self.eat_word("c") {
+            Some(_) => {
+                let (crit, span) = self.expect_number(&["a crit range"])?;
+                if !CRIT_RANGES.contains(&crit) {
+                    return Err(ParseError::new(InvalidNumber, self.s, span)
+                        .expecting(&["a crit range from 2 to 20"]));
+                }
+                Some(crit)
+            }
+            None => None,
+        };
+
+        let min = match self.peek() {
+            Some(t) if t.space_before && self.is_word("min") => {
+                self.next();
+                let (min, span) = self.expect_number(&["a number"])?;
+                if !D20_MINIMUMS.contains(&min) {
+                    return Err(ParseError::new(InvalidNumber, self.s, span)
+                        .expecting(&["a minimum from 0 to 20"]));
+                }
+                Some(min)
+            }
+            _ => None,
+        };
+
+        let target = self.target()?;
+
+        Ok(CheckExpr {
+            adv,
+            modifier,
+            crit,
+            min,
+            target,
+        })
+    }
+
+    /// Parse the target number at the end of a check or attack, eg. the
+    /// `vs 15` in `r+5 vs 15` or the `vs AC 16` in `r+7?1d8+4 vs AC 16`.
+    fn target(&mut self) -> Result<Option<Score>, ParseError> {
+        match self.peek() {
+            Some(t) if t.space_before && self.is_word("vs") => self.next(),
+            _ => return Ok(None),
+        };
+
+        // The target can be labelled as an AC or a DC
+        if self.eat_word("ac").is_none() {
+            self.eat_word("dc");
+        }
+
+        Ok(Some(self.expect_number(&["a number", "AC", "DC"])?.0))
+    }
+
+    /// Parse a sum of terms followed by a crit rule, like the damage of
+    /// an attack. If `required` isn't set, the sum can be empty.
+    fn damage_with_rule(&mut self, required: bool) -> Result<DamageExpr, ParseError> {
+        let mut damage = self.damage(required)?;
+        damage.crit_rule = self.crit_rule(damage.terms.is_empty(), damage.dice_sets())?;
+        Ok(damage)
+    }
+
+    /// Parse the crit rule after some damage, if there is one, eg. the
+    /// `crit max` in `2d6+3 crit max`. The rule has to be separated from
+    /// the damage before it, unless `first` is set. `sets` is the number
+    /// of sets of dice in the damage.
+    fn crit_rule(&mut self, first: bool, sets: usize) -> Result<Option<CritRule>, ParseError> {
+        match self.peek() {
+            Some(t) if (t.space_before || first) && self.is_word("crit") => self.next(),
+            _ => return Ok(None),
+        };
+
+        let rule = if self.eat_word("dice").is_some() {
+            CritRule::DoubleDice
+        } else if self.eat_word("max").is_some() {
+            CritRule::MaxPlusRoll
+        } else if self.eat_word("total").is_some() {
+            CritRule::DoubleTotal
+        } else if self.eat_word("none").is_some() {
+            CritRule::OnlyParts(vec![])
+        } else if self.at(TokenKind::Number) {
+            // The sets of dice that are rolled twice, eg. `crit 0,2`
+            let mut parts = vec![];
+            loop {
+                let (part, span) = self.expect_number(&["a set of dice"])?;
+                if part >= sets || parts.contains(&part) {
+                    return Err(ParseError::new(InvalidNumber, self.s, span)
+                        .expecting(&["a set of dice in the damage"]));
+                }
+                parts.push(part);
+                if self.eat(TokenKind::Comma).is_none() {
+                    break;
+                }
+            }
+            CritRule::OnlyParts(parts)
+        } else {
+            return Err(
+                ParseError::new(UnknownToken, self.s, self.here()).expecting(&[
+                    "dice",
+                    "max",
+                    "total",
+                    "none",
+                    "a set of dice",
+                ]),
+            );
+        };
+
+        Ok(Some(rule))
+    }
+
+    /// Parse a sum of terms. If `required` isn't set, the sum can be
+    /// empty.
+    fn damage(&mut self, required: bool) -> Result<DamageExpr, ParseError> {
+        let mut terms = vec![];
+        if !required && !self.at_term() {
+            return Ok(DamageExpr {
+                terms,
+                crit_rule: None,
+            });
+        }
+
+        terms.push(self.term(None)?);
+        while let Some(op) = self.peek().filter(|t| is_operator(t.kind)).cloned() {
+            self.next();
+            let mut term = self.term(Some(&op))?;
+            if op.kind == TokenKind::Minus {
+                term.negative = !term.negative;
+            }
+            terms.push(term);
+        }
+
+        Ok(DamageExpr {
+            terms,
+            crit_rule: None,
+        })
+    }
+
+    /// Parse a single term, which comes after the operator `op` (or at
+    /// the start of the sum if there isn't one).
+    fn term(&mut self, op: Option<&Token>) -> Result<Term, ParseError> {
+        let start = self.here().start;
+
+        // A term can be negative at the start of the sum or after a +
+        let minus = match op {
+            Some(op) if op.kind == TokenKind::Minus => None,
+            _ => self.eat(TokenKind::Minus),
+        };
+        let op = minus.clone().or_else(|| op.map(|op| op.span.clone()));
+
+        let atom = match self.atom()? {
+            Some(atom) => atom,
+            None => {
+                // Catch operators with nothing after them, like `2d6+`,
+                // `2d6++3` or `(2d6-)`.
+                let dangling = match self.peek() {
+                    None => true,
+                    Some(t) => is_operator(t.kind) || is_closer(t.kind),
+                };
+                return Err(match op {
+                    Some(op) if dangling => ParseError::new(DanglingOperator, self.s, op),
+                    _ => ParseError::new(UnknownToken, self.s, self.here()),
+                }
+                .expecting(TERM));
+            }
+        };
+
+        // A scaled group can't have types inside it, since its rounded
+        // total can't be split between them.
+        let atom = self.scale(atom)?;
+        if let Atom::Scaled(..) = atom {
+            if atom.has_types() {
+                return Err(ParseError::new(Unexpected, self.s, start..self.end));
+            }
+        }
+
+        let damage_type = self.damage_type()?;
+        if damage_type.is_some() && atom.has_types() {
+            // A group with a type can't have types inside it
+            let last = self.tokens[self.pos - 1].span.clone();
+            return Err(ParseError::new(Unexpected, self.s, last));
+        }
+
+        Ok(Term {
+            negative: minus.is_some(),
+            atom,
+            damage_type,
+            span: start..self.end,
+        })
+    }
+
+    fn atom(&mut self) -> Result<Option<Atom>, ParseError> {
+        match self.peek().map(|t| t.kind) {
+            Some(TokenKind::LParen) => {
+                self.next();
+                let inner = self.damage(true)?;
+                self.expect(TokenKind::RParen, &[")"])?;
+                Ok(Some(Atom::Group(inner)))
+            }
+            Some(TokenKind::Number) if self.at_die_after() => {
+                let (num, count) = self.expect_number(&["a number"])?;
+                self.dice(num, count).map(Some)
+            }
+            Some(TokenKind::Number) => Ok(Some(Atom::Number(self.expect_number(TERM)?.0))),
+            Some(TokenKind::Variable) => {
+                let span = self.next().unwrap_or_default();
+                Ok(Some(Atom::Variable(
+                    self.s[span.start + 1..span.end].to_string(),
+                )))
+            }
+            Some(TokenKind::Word) if self.at_die() => {
+                // `d8` is the same as `1d8`
+                let here = self.here();
+                self.dice(1, here.start..here.start).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Parse any multiplications and divisions after `atom`, eg. the
+    /// `*2` in `(2d6+3)*2`.
+    fn scale(&mut self, mut atom: Atom) -> Result<Atom, ParseError> {
+        while let Some(op) = self
+            .peek()
+            .filter(|t| t.kind == TokenKind::Star || t.kind == TokenKind::Slash)
+            .cloned()
+        {
+            self.next();
+            if !self.at(TokenKind::Number) {
+                return Err(
+                    ParseError::new(DanglingOperator, self.s, op.span).expecting(&["a number"])
+                );
+            }
+            let (n, span) = self.expect_number::<Score>(&["a number"])?;
+
+            let scale = match op.kind {
+                TokenKind::Star => Scale::Mul(n),
+                _ if n == 0 => {
+                    return Err(ParseError::new(InvalidNumber, self.s, span)
+                        .expecting(&["a number other than 0"]))
+                }
+                _ => Scale::div(n),
+            };
+            atom = Atom::Scaled(Box::new(atom), scale);
+        }
+
+        Ok(atom)
+    }
+
+    /// Parse the `d8` of a set of dice, after the number of dice (which
+    /// is at `count`).
+    fn dice(&mut self, num: u32, count: Range<usize>) -> Result<Atom, ParseError> {
+        self.next();
+        let sides = match self.peek() {
+            Some(t) if t.kind == TokenKind::Number && !t.space_before => {
+                self.expect_number::<Sides>(&["a number of sides"])?.0
+            }
+            _ => {
+                return Err(ParseError::new(UnknownToken, self.s, self.here())
+                    .expecting(&["a number of sides"]))
+            }
+        };
+
+        let die = count.start..self.end;
+        if num == 0 {
+            return Err(ParseError::new(EmptyDiceCount, self.s, die));
+        }
+        if sides == 0 {
+            return Err(ParseError::new(ZeroSidedDie, self.s, die));
+        }
+
+        Ok(Atom::Dice(num, sides, self.rules(num, sides)?))
+    }
+
+    /// Parse the rules that come after `num` dice with `sides` sides,
+    /// eg. the `kh3` in `4d6kh3`.
+    fn rules(&mut self, num: u32, sides: Sides) -> Result<Option<DiceRules>, ParseError> {
+        let mut rules = DiceRules::default();
+        let mut any = false;
+
+        while let Some(t) = self.peek().filter(|t| !t.space_before) {
+            let start = t.span.start;
+            let duplicate;
+
+            if let Some(rule) = ["kh", "kl", "dh", "dl"]
+                .iter()
+                .find(|rule| self.at_prefix(rule))
+            {
+                self.eat_prefix(rule);
+                let count = match self.peek() {
+                    Some(t) if t.kind == TokenKind::Number && !t.space_before => {
+                        self.expect_number(&["a number"])?.0
+                    }
+                    _ => 1,
+                };
+                let keep = match *rule {
+                    "kh" => KeepRule::KeepHighest(count),
+                    "kl" => KeepRule::KeepLowest(count),
+                    "dh" => KeepRule::DropHighest(count),
+                    _ => KeepRule::DropLowest(count),
+                };
+
+                // Keeping none of the dice, or more dice than were rolled
+                if !keep.fits(num) {
+                    return Err(ParseError::new(InvalidDiceRule, self.s, start..self.end));
+                }
+                duplicate = rules.keep.replace(keep).is_some();
+            } else if self.eat(TokenKind::Bang).is_some() {
+                let threshold = match self.eat(TokenKind::Greater) {
+                    Some(_) => self.expect_number(&["a number"])?.0,
+                    None => sides.abs(),
+                };
+
+                // Every roll would explode
+                if threshold <= 1 {
+                    return Err(ParseError::new(InvalidDiceRule, self.s, start..self.end));
+                }
+                duplicate = rules.explode.replace(threshold).is_some();
+            } else if (self.is_word("r") || self.is_word("rr"))
+                && self.peek_at(1).map(|t| t.kind) == Some(TokenKind::Less)
+            {
+                let recursive = self.is_word("rr");
+                self.next();
+                self.next();
+                let reroll = Reroll {
+                    threshold: self.expect_number(&["a number"])?.0,
+                    recursive,
+                };
+
+                // Every roll would be rerolled forever
+                if reroll.recursive && reroll.threshold >= sides.abs() {
+                    return Err(ParseError::new(InvalidDiceRule, self.s, start..self.end));
+                }
+                duplicate = rules.reroll.replace(reroll).is_some();
+            } else if self.eat_prefix("min").is_some() {
+                let min = self.expect_number(&["a number"])?.0;
+
+                // The dice could never roll the minimum
+                if min > sides.abs() {
+                    return Err(ParseError::new(InvalidDiceRule, self.s, start..self.end));
+                }
+                duplicate = rules.min.replace(min).is_some();
+            } else {
+                break;
+            }
+
+            if duplicate {
+                return Err(ParseError::new(InvalidDiceRule, self.s, start..self.end));
+            }
+            any = true;
+        }
+
+        Ok(if any { Some(rules) } else { None })
+    }
+
+    /// Parse the damage type after a term, if there is one.
+    fn damage_type(&mut self) -> Result<Option<DamageType>, ParseError> {
+        let t = match self.peek() {
+            Some(t) if t.kind == TokenKind::Word => t.clone(),
+            _ => return Ok(None),
+        };
+
+        let word = self.text(&t).to_ascii_lowercase();
+        match word.parse() {
+            Ok(damage_type) => {
+                self.next();
+                Ok(Some(damage_type))
+            }
+            // A separate word that can't come after damage was probably
+            // meant to be a damage type.
+            Err(err)
+                if t.space_before && !self.in_check && !FOLLOW_WORDS.contains(&word.as_str()) =>
+            {
+                Err(err.shifted(t.span.start))
+            }
+            Err(_) => Ok(None),
+        }
+    }
+
+    /* Lookahead */
+
+    /// Whether the next token is a check, rather than damage.
+    fn at_check(&self) -> bool {
+        match self.peek() {
+            Some(t) if is_operator(t.kind) => true,
+            Some(t) if t.kind == TokenKind::Word => {
+                let first = self.text(t).as_bytes()[0].to_ascii_lowercase();
+                // `d8` on its own is a die, not a check with disadvantage,
+                // but `d1d4` is a check with 1d4 added
+                b"rad".contains(&first) && (!self.at_die() || self.at_dice_after_die())
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether the next token can start a term.
+    fn at_term(&self) -> bool {
+        match self.peek().map(|t| t.kind) {
+            Some(TokenKind::Number)
+            | Some(TokenKind::Variable)
+            | Some(TokenKind::Minus)
+            | Some(TokenKind::LParen) => true,
+            Some(TokenKind::Word) => self.at_die(),
+            _ => false,
+        }
+    }
+
+    /// Whether the next tokens are a `d` followed by a number of sides.
+    fn at_die(&self) -> bool {
+        self.is_word("d")
+            && matches!(self.peek_at(1), Some(t) if t.kind == TokenKind::Number && !t.space_before)
+    }
+
+    /// Whether the die at the next tokens is directly followed by a
+    /// `d`, so that its number of sides is really the number of dice,
+    /// eg. `d1d4`.
+    fn at_dice_after_die(&self) -> bool {
+        matches!(self.peek_at(2), Some(t) if t.kind == TokenKind::Word
+            && !t.space_before
+            && self.text(t).eq_ignore_ascii_case("d"))
+    }
+
+    /// Whether the token after the next one is a `d` that directly
+    /// follows it.
+    fn at_die_after(&self) -> bool {
+        matches!(self.peek_at(1), Some(t) if t.kind == TokenKind::Word
+            && !t.space_before
+            && self.text(t).eq_ignore_ascii_case("d"))
+    }
+
+    /// Whether the token after the next one is a word starting with
+    /// `prefix` that directly follows it.
+    fn at_prefix_after(&self, prefix: &str) -> bool {
+        matches!(self.peek_at(1), Some(t) if t.kind == TokenKind::Word
+            && !t.space_before
+            && starts_with(self.text(t), prefix))
+    }
+
+    /// Whether the next token is a word starting with `prefix`.
+    fn at_prefix(&self, prefix: &str) -> bool {
+        matches!(self.peek(), Some(t) if t.kind == TokenKind::Word && starts_with(self.text(t), prefix))
+    }
+
+    /// Whether the next token is the word `word`.
+    fn is_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Some(t) if t.kind == TokenKind::Word && self.text(t).eq_ignore_ascii_case(word))
+    }
+
+    fn at(&self, kind: TokenKind) -> bool {
+        self.peek().map(|t| t.kind) == Some(kind)
+    }
+
+    /* Token handling */
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_at(&self, n: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + n)
+    }
+
+    fn text(&self, t: &Token) -> &'a str {
+        &self.s[t.span.clone()]
+    }
+
+    /// Get the span of the next token, or an empty span at the end of
+    /// the expression if there isn't one.
+    fn here(&self) -> Range<usize> {
+        match self.peek() {
+            Some(t) => t.span.clone(),
+            None => self.s.len()..self.s.len(),
+        }
+    }
+
+    fn next(&mut self) -> Option<Range<usize>> {
+        let span = self.peek()?.span.clone();
+        self.pos += 1;
+        self.end = span.end;
+        Some(span)
+    }
+
+    fn eat(&mut self, kind: TokenKind) -> Option<Range<usize>> {
+        if self.at(kind) {
+            self.next()
+        } else {
+            None
+        }
+    }
+
+    fn eat_word(&mut self, word: &str) -> Option<Range<usize>> {
+        if self.is_word(word) {
+            self.next()
+        } else {
+            None
+        }
+    }
+
+    /// Take `prefix` off the start of the next word, eg. the `x` in
+    /// `2xr+5` or the `d` in `dc19`.
+    fn eat_prefix(&mut self, prefix: &str) -> Option<Range<usize>> {
+        if self.is_word(prefix) {
+            return self.next();
+        } else if !self.at_prefix(prefix) {
+            return None;
+        }
+
+        let t = &mut self.tokens[self.pos];
+        let span = t.span.start..t.span.start + prefix.len();
+        t.span.start = span.end;
+        t.space_before = false;
+        self.end = span.end;
+        Some(span)
+    }
+
+    fn expect(
+        &mut self,
+        kind: TokenKind,
+        expected: &[&'static str],
+    ) -> Result<Range<usize>, ParseError> {
+        self.eat(kind)
+            .ok_or_else(|| ParseError::new(UnknownToken, self.s, self.here()).expecting(expected))
+    }
+
+    fn expect_word(&mut self, word: &'static str) -> Result<Range<usize>, ParseError> {
+        self.eat_word(word)
+            .ok_or_else(|| ParseError::new(UnknownToken, self.s, self.here()).expecting(&[word]))
+    }
+
+    fn expect_number<T: FromStr>(
+        &mut self,
+        expected: &[&'static str],
+    ) -> Result<(T, Range<usize>), ParseError> {
+        let span = self.expect(TokenKind::Number, expected)?;
+        let n = self.s[span.clone()]
+            .parse()
+            .map_err(|_| ParseError::new(InvalidNumber, self.s, span.clone()))?;
+        Ok((n, span))
+    }
+}
+
+fn is_operator(kind: TokenKind) -> bool {
+    kind == TokenKind::Plus || kind == TokenKind::Minus
+}
+
+/// Whether a token of this kind ends an expression.
+fn is_closer(kind: TokenKind) -> bool {
+    kind == TokenKind::RParen || kind == TokenKind::Question || kind == TokenKind::Colon
+}
+
+/// Whether `word` starts with `prefix`, ignoring case.
+fn starts_with(word: &str, prefix: &str) -> bool {
+    word.len() >= prefix.len() && word[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
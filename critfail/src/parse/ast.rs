@@ -0,0 +1,114 @@
+use std::ops::Range;
+
+use crate::DiceRules;
+use crate::{Ability, AdvState, CritRule, DamageType, SaveEffect, Scale, Score, Sides};
+
+/// Any roll expression.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) enum Expr {
+    Check(CheckExpr),
+    Damage(DamageExpr),
+    Attack(AttackExpr),
+    Save(SaveExpr),
+    Repeated(u32, Box<Expr>),
+}
+
+/// A check, eg. `a+5c19 min10 vs 15`.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub(crate) struct CheckExpr {
+    pub(crate) adv: AdvState,
+    pub(crate) modifier: DamageExpr,
+    pub(crate) crit: Option<Score>,
+    pub(crate) min: Option<Score>,
+    pub(crate) target: Option<Score>,
+}
+
+/// A sum of terms, eg. `2d6+3 fire - 1`.
+#[derive(PartialEq, Debug, Clone, Default)]
+pub(crate) struct DamageExpr {
+    pub(crate) terms: Vec<Term>,
+    /// The crit rule written after the sum, eg. the `crit max` in
+    /// `2d6+3 crit max`. Only the damage of a whole expression can have
+    /// one.
+    pub(crate) crit_rule: Option<CritRule>,
+}
+
+/// A single term of a sum, eg. the `-1d4 fire` in `2d6-1d4 fire`.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct Term {
+    pub(crate) negative: bool,
+    pub(crate) atom: Atom,
+    pub(crate) damage_type: Option<DamageType>,
+    pub(crate) span: Range<usize>,
+}
+
+/// The value of a term.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) enum Atom {
+    /// A set of dice, eg. `4d6kh3`.
+    Dice(u32, Sides, Option<DiceRules>),
+    /// A constant, eg. `3`.
+    Number(Score),
+    /// A named value that is given when the expression is rolled, eg.
+    /// `$str`. The name doesn't include the `$`.
+    Variable(String),
+    /// A parenthesised sum, eg. `(1d6+2)`.
+    Group(DamageExpr),
+    /// Another atom multiplied or divided by a number, eg. `8d6/2` or
+    /// `(2d6+3)*2`.
+    Scaled(Box<Atom>, Scale),
+}
+
+/// An attack, eg. `r+5?1d8+3 vs AC 15`.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct AttackExpr {
+    pub(crate) check: CheckExpr,
+    pub(crate) damage: DamageExpr,
+}
+
+/// A save-based effect, eg. `save DC15 dex +3 : 8d6 fire half`.
+#[derive(PartialEq, Debug, Clone)]
+pub(crate) struct SaveExpr {
+    pub(crate) dc: Score,
+    pub(crate) ability: Option<Ability>,
+    pub(crate) check: CheckExpr,
+    pub(crate) damage: DamageExpr,
+    pub(crate) effect: SaveEffect,
+}
+
+impl DamageExpr {
+    /// Whether any of the terms in this sum (or in the groups inside
+    /// it) have a damage type.
+    pub(crate) fn has_types(&self) -> bool {
+        self.terms
+            .iter()
+            .any(|term| term.damage_type.is_some() || term.atom.has_types())
+    }
+
+    /// Count the sets of dice in this sum, including the ones inside
+    /// groups.
+    pub(crate) fn dice_sets(&self) -> usize {
+        self.terms.iter().map(|term| term.atom.dice_sets()).sum()
+    }
+}
+
+impl Atom {
+    /// Whether there are any damage types inside this atom.
+    pub(crate) fn has_types(&self) -> bool {
+        match self {
+            Atom::Group(inner) => inner.has_types(),
+            Atom::Scaled(atom, _) => atom.has_types(),
+            _ => false,
+        }
+    }
+
+    /// Count the sets of dice inside this atom.
+    pub(crate) fn dice_sets(&self) -> usize {
+        match self {
+            Atom::Dice(..) => 1,
+            Atom::Group(inner) => inner.dice_sets(),
+            Atom::Scaled(atom, _) => atom.dice_sets(),
+            _ => 0,
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::ops::Range;
+
+use crate::ParseError;
+use crate::ParseErrorKind::*;
+
+/// The different kinds of token that make up a roll expression.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub(crate) enum TokenKind {
+    /// A run of digits, eg. the `12` in `1d12`.
+    Number,
+    /// A run of letters, eg. the `d` in `1d12` or `fire`.
+    Word,
+    /// A `$` followed by a name, eg. `$str`.
+    Variable,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    LParen,
+    RParen,
+    Question,
+    Colon,
+    Comma,
+    Bang,
+    Less,
+    Greater,
+}
+
+/// A single token of a roll expression.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub(crate) struct Token {
+    pub(crate) kind: TokenKind,
+    /// The byte range of the token in the expression.
+    pub(crate) span: Range<usize>,
+    /// Whether there was whitespace between this token and the one
+    /// before it.
+    pub(crate) space_before: bool,
+}
+
+/// Split a roll expression into tokens.
+pub(crate) fn tokenize(s: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = vec![];
+    let mut chars = s.char_indices().peekable();
+    let mut space_before = false;
+
+    while let Some((start, c)) = chars.next() {
+        if c.is_whitespace() {
+            space_before = true;
+            continue;
+        }
+
+        let kind = match c {
+            '0'..='9' => TokenKind::Number,
+            'a'..='z' | 'A'..='Z' => TokenKind::Word,
+            '+' => TokenKind::Plus,
+            '-' => TokenKind::Minus,
+            '*' => TokenKind::Star,
+            '/' => TokenKind::Slash,
+            '(' => TokenKind::LParen,
+            ')' => TokenKind::RParen,
+            '?' => TokenKind::Question,
+            ':' => TokenKind::Colon,
+            ',' => TokenKind::Comma,
+            '!' => TokenKind::Bang,
+            '<' => TokenKind::Less,
+            '>' => TokenKind::Greater,
+            '$' => TokenKind::Variable,
+            _ => {
+                return Err(ParseError::new(
+                    UnknownToken,
+                    s,
+                    start..start + c.len_utf8(),
+                ))
+            }
+        };
+
+        // Numbers, words and variables carry on for as long as the same
+        // kind of character does.
+        let mut end = start + c.len_utf8();
+        if kind == TokenKind::Number || kind == TokenKind::Word || kind == TokenKind::Variable {
+            while let Some(&(i, next)) = chars.peek() {
+                let same = match kind {
+                    TokenKind::Number => next.is_ascii_digit(),
+                    TokenKind::Word => next.is_ascii_alphabetic(),
+                    _ => next.is_ascii_alphanumeric() || next == '_',
+                };
+                if !same {
+                    break;
+                }
+                end = i + next.len_utf8();
+                chars.next();
+            }
+        }
+
+        // A variable needs a name
+        if kind == TokenKind::Variable && end == start + 1 {
+            return Err(ParseError::new(UnknownToken, s, start..end).expecting(&["a name"]));
+        }
+
+        tokens.push(Token {
+            kind,
+            span: start..end,
+            space_before,
+        });
+        space_before = false;
+    }
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TokenKind::*;
+
+    fn kinds(s: &str) -> Vec<(TokenKind, &str, bool)> {
+        tokenize(s)
+            .unwrap()
+            .into_iter()
+            .map(|t| (t.kind, &s[t.span], t.space_before))
+            .collect()
+    }
+
+    #[test]
+    fn dice() {
+        assert_eq!(
+            kinds("4d6kh3"),
+            vec![
+                (Number, "4", false),
+                (Word, "d", false),
+                (Number, "6", false),
+                (Word, "kh", false),
+                (Number, "3", false),
+            ]
+        );
+        assert_eq!(
+            kinds("1D8 + 3 Fire"),
+            vec![
+                (Number, "1", false),
+                (Word, "D", false),
+                (Number, "8", false),
+                (Plus, "+", true),
+                (Number, "3", true),
+                (Word, "Fire", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn punctuation() {
+        assert_eq!(
+            kinds("(r-2)*3/2?1d6!>5 :0,1"),
+            vec![
+                (LParen, "(", false),
+                (Word, "r", false),
+                (Minus, "-", false),
+                (Number, "2", false),
+                (RParen, ")", false),
+                (Star, "*", false),
+                (Number, "3", false),
+                (Slash, "/", false),
+                (Number, "2", false),
+                (Question, "?", false),
+                (Number, "1", false),
+                (Word, "d", false),
+                (Number, "6", false),
+                (Bang, "!", false),
+                (Greater, ">", false),
+                (Number, "5", false),
+                (Colon, ":", true),
+                (Number, "0", false),
+                (Comma, ",", false),
+                (Number, "1", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn variables() {
+        assert_eq!(
+            kinds("r+$str+$prof_2?1d8"),
+            vec![
+                (Word, "r", false),
+                (Plus, "+", false),
+                (Variable, "$str", false),
+                (Plus, "+", false),
+                (Variable, "$prof_2", false),
+                (Question, "?", false),
+                (Number, "1", false),
+                (Word, "d", false),
+                (Number, "8", false),
+            ]
+        );
+
+        let err = tokenize("r+$ +2").unwrap_err();
+        assert_eq!(err.kind(), UnknownToken);
+        assert_eq!(err.span(), 2..3);
+    }
+
+    #[test]
+    fn unknown() {
+        let err = tokenize("r+5 & 3").unwrap_err();
+        assert_eq!(err.kind(), UnknownToken);
+        assert_eq!(err.span(), 4..5);
+
+        assert!(tokenize("r+é").is_err());
+        assert_eq!(tokenize("  ").unwrap(), vec![]);
+    }
+}
@@ -1,12 +1,18 @@
+use rand::Rng;
+use std::collections::HashMap;
+use std::fmt;
+
 use crate::RollExpression;
-use crate::{Attack, Check, Damage};
+use crate::{AdvState, Attack, Check, Damage, Save, Score, UnboundVariable};
 
+pub use repeatedoutcome::RepeatedOutcome;
 pub use rolloutcome::RollOutcome;
 
+mod repeatedoutcome;
 mod rolloutcome;
 mod rollparse;
 
-/// Any kind of roll—either a check, damage, or attack roll.
+/// Any kind of roll—either a check, damage, attack, or save roll.
 ///
 /// This struct is useful when parsing a roll expression if you don't
 /// know what type of roll expression it will be.
@@ -28,8 +34,14 @@ mod rollparse;
 /// let attack_outcome = attack.roll();
 /// print!("{}", attack_outcome); // eg. "10 ? 16"
 /// print!("{:?}", attack_outcome); // eg. "(9)+1 ? [6+6]+4"
+///
+/// let multiattack = Roll::new("2x r+1?2d6+4").unwrap();
+/// let multiattack_outcome = multiattack.roll();
+/// print!("{}", multiattack_outcome); // eg. "10 ? 16, 4 ? 9 (total 25)"
+/// print!("{:?}", multiattack_outcome); // eg. "(9)+1 ? [6+6]+4, (3)+1 ? [1+4]+4"
 /// ```
 #[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Roll {
     /// A `Roll` containing a `Check` roll.
     Check(Check),
@@ -37,16 +49,24 @@ pub enum Roll {
     Damage(Damage),
     /// A `Roll` containing an `Attack` roll.
     Attack(Attack),
+    /// A `Roll` containing a `Save` roll.
+    Save(Save),
+    /// A `Roll` that is rolled several times (eg. `3x r+5?1d8+3`).
+    Repeated(u32, Box<Roll>),
 }
 
 impl RollExpression for Roll {
     type Outcome = RollOutcome;
 
-    fn roll(&self) -> Self::Outcome {
+    fn roll_with_rng<R: Rng + ?Sized>(&self, rng: &mut R) -> Self::Outcome {
         match self {
-            Roll::Check(c) => c.roll().into(),
-            Roll::Damage(d) => d.roll().into(),
-            Roll::Attack(a) => a.roll().into(),
+            Roll::Check(c) => c.roll_with_rng(rng).into(),
+            Roll::Damage(d) => d.roll_with_rng(rng).into(),
+            Roll::Attack(a) => a.roll_with_rng(rng).into(),
+            Roll::Save(s) => s.roll_with_rng(rng).into(),
+            Roll::Repeated(n, roll) => {
+                RepeatedOutcome::new((0..*n).map(|_| roll.roll_with_rng(rng)).collect()).into()
+            }
         }
     }
 }
@@ -90,6 +110,187 @@ impl Roll {
     pub fn is_attack(&self) -> bool {
         matches!(self, Self::Attack(_))
     }
+
+    /// Return true if this `Roll` is a save roll.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Roll};
+    ///
+    /// assert_eq!(Roll::new("r+3?2d8+5").unwrap().is_save(), false);
+    /// assert_eq!(Roll::new("save DC15 dex +3 : 8d6").unwrap().is_save(), true);
+    /// ```
+    pub fn is_save(&self) -> bool {
+        matches!(self, Self::Save(_))
+    }
+
+    /// Return true if this `Roll` is a repeated roll.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Roll};
+    ///
+    /// assert_eq!(Roll::new("r+3?2d8+5").unwrap().is_repeated(), false);
+    /// assert_eq!(Roll::new("2x r+3?2d8+5").unwrap().is_repeated(), true);
+    /// ```
+    pub fn is_repeated(&self) -> bool {
+        matches!(self, Self::Repeated(..))
+    }
+
+    /// Return true if this `Roll` rolls a d20 that can be rolled with
+    /// advantage (ie. it is a check, attack or save, or a repeated
+    /// check, attack or save).
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Roll};
+    ///
+    /// assert_eq!(Roll::new("r+3").unwrap().can_have_advantage(), true);
+    /// assert_eq!(Roll::new("2x r+3?2d8+5").unwrap().can_have_advantage(), true);
+    /// assert_eq!(Roll::new("2d8+5").unwrap().can_have_advantage(), false);
+    /// ```
+    pub fn can_have_advantage(&self) -> bool {
+        match self {
+            Self::Check(_) | Self::Attack(_) | Self::Save(_) => true,
+            Self::Damage(_) => false,
+            Self::Repeated(_, roll) => roll.can_have_advantage(),
+        }
+    }
+
+    /// Get the names of the variables in this roll (without the `$`),
+    /// in the order they first appear. These need to be given values
+    /// before the roll can be rolled with `roll_with_context()`.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Roll};
+    ///
+    /// let roll = Roll::new("r+$str+$prof?1d8+$str").unwrap();
+    /// assert_eq!(roll.variables(), vec!["str", "prof"]);
+    /// assert!(Roll::new("r+5?1d8+3").unwrap().variables().is_empty());
+    /// ```
+    pub fn variables(&self) -> Vec<&str> {
+        match self {
+            Roll::Check(c) => c.variables(),
+            Roll::Damage(d) => d.variables(),
+            Roll::Attack(a) => a.variables(),
+            Roll::Save(s) => s.variables(),
+            Roll::Repeated(_, roll) => roll.variables(),
+        }
+    }
+
+    /// Replace each variable in this roll with its value in `context`.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Roll};
+    /// use std::collections::HashMap;
+    ///
+    /// let mut context = HashMap::new();
+    /// context.insert("str".to_string(), 3);
+    /// context.insert("prof".to_string(), 2);
+    ///
+    /// let roll = Roll::new("r+$str+$prof?1d8+$str").unwrap();
+    /// assert_eq!(roll.bind(&context).unwrap(), Roll::new("r+3+2?1d8+3").unwrap());
+    /// ```
+    pub fn bind(&self, context: &HashMap<String, Score>) -> Result<Self, UnboundVariable> {
+        Ok(match self {
+            Roll::Check(c) => Roll::Check(c.bind(context)?),
+            Roll::Damage(d) => Roll::Damage(d.bind(context)?),
+            Roll::Attack(a) => Roll::Attack(a.bind(context)?),
+            Roll::Save(s) => Roll::Save(s.bind(context)?),
+            Roll::Repeated(n, roll) => Roll::Repeated(*n, Box::new(roll.bind(context)?)),
+        })
+    }
+
+    /// Roll this roll, using the values in `context` for its
+    /// variables.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Roll};
+    /// use std::collections::HashMap;
+    ///
+    /// let mut context = HashMap::new();
+    /// context.insert("str".to_string(), 3);
+    ///
+    /// let roll = Roll::new("2d6+$str").unwrap();
+    /// let outcome = roll.roll_with_context(&context).unwrap();
+    /// print!("{:?}", outcome); // Prints something like "[4+1]+3"
+    ///
+    /// assert!(Roll::new("2d6+$dex").unwrap().roll_with_context(&context).is_err());
+    /// ```
+    pub fn roll_with_context(
+        &self,
+        context: &HashMap<String, Score>,
+    ) -> Result<RollOutcome, UnboundVariable> {
+        self.roll_with_context_and_rng(context, &mut rand::thread_rng())
+    }
+
+    /// Roll this roll, using the values in `context` for its variables
+    /// and `rng` as the source of randomness.
+    pub fn roll_with_context_and_rng<R: Rng + ?Sized>(
+        &self,
+        context: &HashMap<String, Score>,
+        rng: &mut R,
+    ) -> Result<RollOutcome, UnboundVariable> {
+        Ok(self.bind(context)?.roll_with_rng(rng))
+    }
+
+    /// Roll this roll using `adv` to override the advantage state of
+    /// any checks or attacks. Damage rolls are rolled normally.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Roll, AdvState};
+    /// let roll = Roll::new("2x r+3?1d8+3").unwrap();
+    ///
+    /// roll.roll_with_advantage(AdvState::Advantage); // Roll both attacks with advantage
+    /// ```
+    pub fn roll_with_advantage(&self, adv: AdvState) -> RollOutcome {
+        self.roll_with_advantage_and_rng(adv, &mut rand::thread_rng())
+    }
+
+    /// Roll this roll using `adv` to override the advantage state of
+    /// any checks or attacks, and `rng` as the source of randomness.
+    pub fn roll_with_advantage_and_rng<R: Rng + ?Sized>(
+        &self,
+        adv: AdvState,
+        rng: &mut R,
+    ) -> RollOutcome {
+        match self {
+            Roll::Check(c) => c.roll_with_advantage_and_rng(adv, rng).into(),
+            Roll::Damage(d) => d.roll_with_rng(rng).into(),
+            Roll::Attack(a) => a.roll_with_advantage_and_rng(adv, rng).into(),
+            Roll::Save(s) => s.roll_with_advantage_and_rng(adv, rng).into(),
+            Roll::Repeated(n, roll) => RepeatedOutcome::new(
+                (0..*n)
+                    .map(|_| roll.roll_with_advantage_and_rng(adv, rng))
+                    .collect(),
+            )
+            .into(),
+        }
+    }
+}
+
+/// Rolls are written in a canonical form of the notation they are
+/// parsed from, so parsing the written roll gives the same `Roll` back.
+///
+/// ```
+/// use critfail::{RollExpression, Roll};
+///
+/// let roll = Roll::new("2x +3?2d8-1").unwrap();
+/// assert_eq!(roll.to_string(), "2x r+3?2d8-1");
+/// assert_eq!(Roll::new(&roll.to_string()).unwrap(), roll);
+/// ```
+impl fmt::Display for Roll {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Roll::Check(c) => write!(f, "{}", c),
+            // Damage starting with a `-` would be parsed as a check, so it
+            // is put in brackets.
+            Roll::Damage(d) => match d.to_string() {
+                d if d.starts_with('-') => write!(f, "({})", d),
+                d => write!(f, "{}", d),
+            },
+            Roll::Attack(a) => write!(f, "{}", a),
+            Roll::Save(s) => write!(f, "{}", s),
+            Roll::Repeated(n, roll) => write!(f, "{}x {}", n, roll),
+        }
+    }
 }
 
 impl From<Check> for Roll {
@@ -109,3 +310,284 @@ impl From<Attack> for Roll {
         Self::Attack(attack)
     }
 }
+
+impl From<Save> for Roll {
+    fn from(save: Save) -> Self {
+        Self::Save(save)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CritRule;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn seeded_rolls_repeat() {
+        for expression in &[
+            "r+3",
+            "a-1+1d4",
+            "4d6+2-1d4",
+            "d+5?2d8+3",
+            "3x r+5?1d8+3",
+            "save DC15 dex +3 : 8d6 half",
+        ] {
+            let roll = Roll::new(expression).unwrap();
+
+            let first = roll.roll_with_rng(&mut StdRng::seed_from_u64(5));
+            let second = roll.roll_with_rng(&mut StdRng::seed_from_u64(5));
+
+            assert_eq!(first, second);
+        }
+    }
+
+    #[test]
+    fn seeded_crit_rolls_repeat() {
+        let damage = Damage::new("2d8+3-1d4").unwrap();
+
+        let first = damage.crit_roll_with_rng(&mut StdRng::seed_from_u64(9));
+        let second = damage.crit_roll_with_rng(&mut StdRng::seed_from_u64(9));
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn misses_roll_damage() {
+        use rand::Rng;
+
+        // The damage is rolled whether the attack hits or misses, so the
+        // rolls after it are the same either way.
+        let hit = Attack::new("r+5?2d8+3").unwrap();
+        let miss = Attack::new("r+5?2d8+3 vs AC 30").unwrap();
+
+        let mut hit_rng = StdRng::seed_from_u64(3);
+        let mut miss_rng = StdRng::seed_from_u64(3);
+        hit.roll_with_rng(&mut hit_rng);
+        let outcome = miss.roll_with_rng(&mut miss_rng);
+
+        assert_eq!(outcome.hit(), Some(false));
+        assert_eq!(outcome.damage().score(), 0);
+        assert_eq!(hit_rng.gen::<u64>(), miss_rng.gen::<u64>());
+    }
+
+    #[test]
+    fn display() {
+        for &(expression, display) in &[
+            ("+3?2d8-1", "r+3?2d8-1"),
+            ("r", "r"),
+            ("a + 5 + 1d4 c19 min 10 vs DC 15", "a+5+1d4c19 min10 vs 15"),
+            ("d-1", "d-1"),
+            ("d6", "1d6"),
+            ("4d6kh + 2d6r<2 - 1d4", "4d6kh1+2d6r<2-1d4"),
+            ("1d6!>6 + 1d10!>8rr<1min2", "1d6!+1d10!>8rr<1min2"),
+            ("1d8 + 3 slashing + (2d6) fire", "1d8+3 slashing+2d6 fire"),
+            ("(1d6 + 2) cold + 1", "1d6+2 cold+1"),
+            ("2d8 - 1d4/2", "2d8-1d4/2"),
+            ("(-1d4)/2 + (2d6+3)*2 fire", "(-1d4)/2+(2d6+3)*2 fire"),
+            ("(-(1d6*2)/3 + (-(1d6)/2)*3)", "(-1d6*2/3+(-1d6/2)*3)"),
+            ("($str - 2) + -$prof*2", "$str-2-$prof*2"),
+            (
+                "+$dex?1d6+$dex piercing vs 14",
+                "r+$dex?1d6+$dex piercing vs AC 14",
+            ),
+            ("save dc 15 dex +3: 8d6", "save DC15 dex r+3 : 8d6 half"),
+            ("save DC12 : 1d12 negates", "save DC12 : 1d12 negates"),
+            ("3x a-1?1d8+3", "3x a-1?1d8+3"),
+        ] {
+            let roll = Roll::new(expression).unwrap();
+            assert_eq!(roll.to_string(), display, "{}", expression);
+        }
+    }
+
+    #[test]
+    fn display_crit_rule() {
+        let attack = Attack::new("r+3?1d8+2d6-1d4").unwrap();
+        for rule in &[
+            CritRule::DoubleDice,
+            CritRule::MaxPlusRoll,
+            CritRule::DoubleTotal,
+            CritRule::OnlyParts(vec![]),
+            CritRule::OnlyParts(vec![2, 0]),
+        ] {
+            let attack = attack.clone().with_crit_rule(rule.clone());
+            let display = attack.to_string();
+            assert_eq!(Attack::new(&display).unwrap(), attack, "{}", display);
+        }
+
+        let attack = attack.with_crit_rule(CritRule::MaxPlusRoll);
+        assert_eq!(attack.to_string(), "r+3?1d8+2d6-1d4 crit max");
+    }
+
+    #[test]
+    fn built_round_trip() {
+        use crate::{AttackBuilder, CheckBuilder, DamageBuilder, DamageType};
+
+        for roll in &[
+            Roll::Damage(Damage::default()),
+            Roll::Damage(
+                DamageBuilder::new()
+                    .crit_rule(CritRule::MaxPlusRoll)
+                    .build(),
+            ),
+            Roll::Damage(
+                DamageBuilder::new()
+                    .dice(2, 6)
+                    .modifier(-3)
+                    .damage_type(DamageType::Fire)
+                    .build(),
+            ),
+            Roll::Check(CheckBuilder::new().build()),
+            Roll::Check(CheckBuilder::new().dice(1, -4).crit(19).min(10).build()),
+            Roll::Attack(AttackBuilder::new().check_modifier(3).build()),
+            Roll::Attack(
+                AttackBuilder::new()
+                    .check_crit(18)
+                    .check_target(15)
+                    .crit_rule(CritRule::OnlyParts(vec![]))
+                    .build(),
+            ),
+            Roll::Repeated(
+                2,
+                Box::new(AttackBuilder::new().damage_dice(1, 8).build().into()),
+            ),
+        ] {
+            let display = roll.to_string();
+            assert_eq!(&Roll::new(&display).unwrap(), roll, "{}", display);
+        }
+    }
+
+    #[test]
+    fn display_round_trip() {
+        for expression in &[
+            "r+3",
+            "rc19",
+            "r min10",
+            "a-1+1d4-$str c18 min10 vs 12",
+            "4d6kh3+1d6!+2d6r<2-1d4min2+3d6dl1!>5",
+            "1d8+1d6 fire+(1d6+2) cold-1d4 necrotic+3 cold",
+            "(2d6+3)*2 fire + 8d6/2 + 7/2",
+            "(-(2d6-3)*2 - (-1d4)/2 - 1d6/2*3 + -((1d6-1)/2)*3)",
+            "-$a+$b*2-($c+1)/2",
+            "(-$a+$b*2-($c+1)/2)",
+            "(-1d4 fire)+2",
+            "2x (-1d6/2)",
+            "d+5?2d8+3 slashing vs AC 12",
+            "r+$str?(1d8+$str)*2",
+            "save DC15 dex a+3c19 : 8d6 fire negates",
+            "save dc 10 : 2d4",
+            "2x save DC13 con : 3d6",
+            "3x r+5?1d8+3",
+            "r+5?2d6+1d8 fire crit 0,1 vs AC 12",
+            "1d6 crit total",
+            "8d6/2 crit none",
+            "save DC15 : 8d6 crit max half",
+        ] {
+            let roll = Roll::new(expression).unwrap();
+            let display = roll.to_string();
+            assert_eq!(
+                Roll::new(&display).unwrap(),
+                roll,
+                "{} -> {}",
+                expression,
+                display
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        for expression in &[
+            "a+5+1d4c19 min10 vs 15",
+            "4d6kh3+1d6!+2d6r<2-1d4min2",
+            "(2d6+3)*2 fire + 8d6/2",
+            "r+$str?1d8+$str",
+            "d+5?2d8+3 slashing vs AC 12",
+            "3x r+5?1d8+3",
+            "save DC15 dex +3 : 8d6 half",
+        ] {
+            let roll = Roll::new(expression).unwrap();
+            let json = serde_json::to_string(&roll).unwrap();
+            assert_eq!(serde_json::from_str::<Roll>(&json).unwrap(), roll);
+
+            let outcome = roll.roll_with_rng(&mut StdRng::seed_from_u64(5));
+            let json = serde_json::to_string(&outcome).unwrap();
+            let back = serde_json::from_str::<RollOutcome>(&json).unwrap();
+            assert_eq!(back, outcome);
+            assert_eq!(format!("{:?}", back), format!("{:?}", outcome));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_keeps_dice() {
+        use crate::DamageOutcomeBuilder;
+        use serde_json::json;
+
+        let outcome = DamageOutcomeBuilder::new()
+            .dice(8, vec![3, 5])
+            .modifier(2)
+            .build();
+
+        assert_eq!(
+            serde_json::to_value(&outcome).unwrap(),
+            json!({
+                "scores": [{ "Dice": [8, [3, 5]] }, { "Modifier": 2 }],
+                "multiplier": 1,
+            })
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_checks_expressions() {
+        use crate::Modifier;
+        use serde_json::{from_value, json};
+
+        let rules = |keep| json!({ "keep": keep, "explode": null, "reroll": null, "min": null });
+        for (valid, invalid) in &[
+            (json!({ "Dice": [2, 6] }), json!({ "Dice": [2, 0] })),
+            (json!({ "Dice": [2, 6] }), json!({ "Dice": [0, 6] })),
+            (
+                json!({ "RuledDice": [4, 6, rules(json!({ "KeepHighest": 3 }))] }),
+                json!({ "RuledDice": [4, 6, rules(json!({ "KeepHighest": 0 }))] }),
+            ),
+            (
+                json!({ "Scaled": [[{ "Constant": 3 }], { "Div": 2 }] }),
+                json!({ "Scaled": [[], { "Div": 2 }] }),
+            ),
+            (
+                json!({ "Scaled": [[{ "Constant": 3 }], { "Div": 2 }] }),
+                json!({ "Scaled": [[{ "Constant": 3 }], { "Div": 0 }] }),
+            ),
+            (
+                json!({ "Variable": { "name": "str", "negative": true } }),
+                json!({ "Variable": { "name": "s tr", "negative": true } }),
+            ),
+        ] {
+            assert!(from_value::<Modifier>(valid.clone()).is_ok(), "{}", valid);
+            assert!(
+                from_value::<Modifier>(invalid.clone()).is_err(),
+                "{}",
+                invalid
+            );
+        }
+
+        let check = |crit, min| json!({ "adv": "Neutral", "modifiers": [], "min": min, "crit": crit, "target": null });
+        assert!(from_value::<Check>(check(json!(19), json!(10))).is_ok());
+        assert!(from_value::<Check>(check(json!(25), json!(null))).is_err());
+        assert!(from_value::<Check>(check(json!(null), json!(-3))).is_err());
+        assert!(from_value::<Check>(check(json!(null), json!(21))).is_err());
+
+        let damage = |sets| {
+            json!({
+                "parts": [{ "modifier": { "Dice": [1, 6] }, "damage_type": null }],
+                "crit_rule": { "OnlyParts": sets },
+            })
+        };
+        assert!(from_value::<Damage>(damage(json!([0]))).is_ok());
+        assert!(from_value::<Damage>(damage(json!([1]))).is_err());
+        assert!(from_value::<Damage>(damage(json!([0, 0]))).is_err());
+    }
+}
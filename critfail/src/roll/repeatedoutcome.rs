@@ -0,0 +1,161 @@
+use std::fmt;
+
+use crate::{CritScore, RollOutcome, Score};
+
+/// The outcome of rolling the same roll expression several times, eg.
+/// `3x r+5?1d8+3`.
+///
+/// This is normally constructed as the result of calling `roll()` on a
+/// `Roll::Repeated` roll expression.
+///
+/// ```
+/// use critfail::{RollExpression, Roll, RollOutcome};
+///
+/// let outcome = Roll::new("3x r+5?1d8+3").unwrap().roll();
+///
+/// if let RollOutcome::Repeated(repeated) = outcome {
+///     assert_eq!(repeated.outcomes().len(), 3);
+///     print!("{}", repeated.total_damage()); // Prints something like "19"
+/// }
+/// ```
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RepeatedOutcome {
+    outcomes: Vec<RollOutcome>,
+}
+
+impl RepeatedOutcome {
+    pub(crate) fn new(outcomes: Vec<RollOutcome>) -> Self {
+        Self { outcomes }
+    }
+
+    /// Get the outcome of each roll, in the order they were rolled.
+    pub fn outcomes(&self) -> &[RollOutcome] {
+        &self.outcomes
+    }
+
+    /// Get the total damage dealt by all the rolls.
+    ///
+    /// Attacks only count their damage if they hit, so attacks that
+    /// miss their AC or roll a critical failure deal no damage. Saves
+    /// count the damage the target takes, and checks don't deal any
+    /// damage.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Roll, RollOutcome};
+    ///
+    /// let outcome = Roll::new("4x 2d6+1").unwrap().roll();
+    ///
+    /// if let RollOutcome::Repeated(repeated) = outcome {
+    ///     assert!(repeated.total_damage() >= 12);
+    ///     assert!(repeated.total_damage() <= 52);
+    /// }
+    /// ```
+    pub fn total_damage(&self) -> Score {
+        self.outcomes.iter().map(damage).sum()
+    }
+
+    /// Whether any of the rolls can deal damage.
+    fn deals_damage(&self) -> bool {
+        self.outcomes
+            .iter()
+            .any(|o| o.is_attack() || o.is_damage() || o.is_save())
+    }
+}
+
+/// Get the damage dealt by a single outcome.
+fn damage(outcome: &RollOutcome) -> Score {
+    match outcome {
+        RollOutcome::Check(_) => 0,
+        RollOutcome::Damage(damage) => damage.score(),
+        // Misses are already zeroed by the outcome, but without an AC a
+        // natural 1 still shows its damage
+        RollOutcome::Attack(attack) => match attack.check().crit_score() {
+            CritScore::Fail => 0,
+            _ => attack.damage().score(),
+        },
+        RollOutcome::Save(save) => save.score(),
+        RollOutcome::Repeated(repeated) => repeated.total_damage(),
+    }
+}
+
+impl fmt::Display for RepeatedOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let outcomes: Vec<String> = self.outcomes.iter().map(|o| format!("{}", o)).collect();
+        write!(f, "{}", outcomes.join(", "))?;
+
+        if self.deals_damage() {
+            write!(f, " (total {})", self.total_damage())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for RepeatedOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let outcomes: Vec<String> = self.outcomes.iter().map(|o| format!("{:?}", o)).collect();
+        write!(f, "{}", outcomes.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AdvState::*;
+    use crate::OutcomePart::Dice as D;
+    use crate::OutcomePart::Modifier as M;
+    use crate::{AttackOutcome, CheckOutcome, DamageOutcome};
+
+    fn attack(d20: Score, target: Option<Score>) -> RollOutcome {
+        AttackOutcome::new(
+            CheckOutcome::new(Neutral, d20, 10, vec![M(5)]).with_target(target),
+            DamageOutcome::new(vec![D(8, vec![4]), M(3)]),
+        )
+        .into()
+    }
+
+    #[test]
+    fn attacks() {
+        let r = RepeatedOutcome::new(vec![
+            attack(12, Some(16)),
+            attack(8, Some(16)),
+            attack(1, None),
+            attack(10, None),
+        ]);
+
+        assert_eq!(r.total_damage(), 14);
+        assert_eq!(
+            format!("{}", r),
+            "Hit (17) ? 7, Miss (13), Fail ? 7, 15 ? 7 (total 14)"
+        );
+        assert_eq!(
+            format!("{:?}", r),
+            "(12)+5 vs 16 ? [4]+3, (8)+5 vs 16 ? Miss, (1)+5 ? [4]+3, (10)+5 ? [4]+3"
+        );
+    }
+
+    #[test]
+    fn checks() {
+        let r = RepeatedOutcome::new(vec![
+            CheckOutcome::new(Neutral, 12, 10, vec![M(2)]).into(),
+            CheckOutcome::new(Neutral, 20, 10, vec![M(2)]).into(),
+        ]);
+
+        assert_eq!(r.total_damage(), 0);
+        assert_eq!(format!("{}", r), "14, Critical");
+        assert_eq!(format!("{:?}", r), "(12)+2, (20)+2");
+    }
+
+    #[test]
+    fn damage() {
+        let r = RepeatedOutcome::new(vec![
+            DamageOutcome::new(vec![D(6, vec![2, 3])]).into(),
+            DamageOutcome::new(vec![D(6, vec![6, 1])]).into(),
+        ]);
+
+        assert_eq!(r.total_damage(), 12);
+        assert_eq!(format!("{}", r), "5, 7 (total 12)");
+        assert_eq!(format!("{:?}", r), "[2+3], [6+1]");
+    }
+}
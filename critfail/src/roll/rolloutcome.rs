@@ -1,8 +1,8 @@
 use std::fmt;
 
-use crate::{AttackOutcome, CheckOutcome, DamageOutcome};
+use crate::{AttackOutcome, CheckOutcome, DamageOutcome, RepeatedOutcome, SaveOutcome};
 
-/// The outcome of rolling a check, damage, or attack roll.
+/// The outcome of rolling a check, damage, attack, or save roll.
 ///
 /// This is normally constructed as the result of calling `roll()` on a
 /// `Roll` roll expression.
@@ -26,6 +26,10 @@ use crate::{AttackOutcome, CheckOutcome, DamageOutcome};
 ///             println!("Check score: {}", attack.check().score());
 ///             println!("Damage score: {}", attack.damage().score())
 ///         }
+///         RollOutcome::Save(save) => println!("Damage taken: {}", save.score()),
+///         RollOutcome::Repeated(repeated) => {
+///             println!("Total damage: {}", repeated.total_damage())
+///         }
 ///     }
 /// }
 ///
@@ -33,7 +37,8 @@ use crate::{AttackOutcome, CheckOutcome, DamageOutcome};
 /// print_score(damage);
 /// print_score(attack);
 /// ```
-#[derive(Clone)]
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RollOutcome {
     /// The outcome of a `Roll` that contained a `Check`.
     Check(CheckOutcome),
@@ -41,6 +46,10 @@ pub enum RollOutcome {
     Damage(DamageOutcome),
     /// The outcome of a `Roll` that contained an `Attack`.
     Attack(AttackOutcome),
+    /// The outcome of a `Roll` that contained a `Save`.
+    Save(SaveOutcome),
+    /// The outcome of a `Roll` that was rolled several times.
+    Repeated(RepeatedOutcome),
 }
 
 impl RollOutcome {
@@ -82,6 +91,31 @@ impl RollOutcome {
     pub fn is_attack(&self) -> bool {
         matches!(self, Self::Attack(_))
     }
+
+    /// Return true if this `RollOutcome` is the outcome of a save roll.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Roll};
+    ///
+    /// assert_eq!(Roll::new("r+3?2d8+5").unwrap().roll().is_save(), false);
+    /// assert_eq!(Roll::new("save DC15 dex +3 : 8d6").unwrap().roll().is_save(), true);
+    /// ```
+    pub fn is_save(&self) -> bool {
+        matches!(self, Self::Save(_))
+    }
+
+    /// Return true if this `RollOutcome` is the outcome of a repeated
+    /// roll.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Roll};
+    ///
+    /// assert_eq!(Roll::new("r+3?2d8+5").unwrap().roll().is_repeated(), false);
+    /// assert_eq!(Roll::new("2x r+3?2d8+5").unwrap().roll().is_repeated(), true);
+    /// ```
+    pub fn is_repeated(&self) -> bool {
+        matches!(self, Self::Repeated(_))
+    }
 }
 
 impl fmt::Display for RollOutcome {
@@ -90,6 +124,8 @@ impl fmt::Display for RollOutcome {
             RollOutcome::Check(c) => write!(f, "{}", c),
             RollOutcome::Damage(d) => write!(f, "{}", d),
             RollOutcome::Attack(a) => write!(f, "{}", a),
+            RollOutcome::Save(s) => write!(f, "{}", s),
+            RollOutcome::Repeated(r) => write!(f, "{}", r),
         }
     }
 }
@@ -100,6 +136,8 @@ impl fmt::Debug for RollOutcome {
             RollOutcome::Check(c) => write!(f, "{:?}", c),
             RollOutcome::Damage(d) => write!(f, "{:?}", d),
             RollOutcome::Attack(a) => write!(f, "{:?}", a),
+            RollOutcome::Save(s) => write!(f, "{:?}", s),
+            RollOutcome::Repeated(r) => write!(f, "{:?}", r),
         }
     }
 }
@@ -121,3 +159,15 @@ impl From<AttackOutcome> for RollOutcome {
         Self::Attack(o)
     }
 }
+
+impl From<SaveOutcome> for RollOutcome {
+    fn from(o: SaveOutcome) -> Self {
+        Self::Save(o)
+    }
+}
+
+impl From<RepeatedOutcome> for RollOutcome {
+    fn from(o: RepeatedOutcome) -> Self {
+        Self::Repeated(o)
+    }
+}
@@ -1,42 +1,53 @@
 use std::str::FromStr;
 
+use crate::parse::{self, Expr};
 use crate::{ParseError, Roll};
 
 impl FromStr for Roll {
     type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.is_empty() {
-            Err(ParseError::new("Empty String"))
-        } else if s.contains('?') {
-            Ok(Roll::Attack(s.parse()?))
-        } else if "rad+-".contains(&s[0..1]) {
-            Ok(Roll::Check(s.parse()?))
-        } else {
-            Ok(Roll::Damage(s.parse()?))
+        Ok(parse::roll(s)?.into())
+    }
+}
+
+impl From<Expr> for Roll {
+    fn from(expr: Expr) -> Self {
+        match expr {
+            Expr::Check(check) => Roll::Check(check.into()),
+            Expr::Damage(damage) => Roll::Damage(damage.into()),
+            Expr::Attack(attack) => Roll::Attack(attack.into()),
+            Expr::Save(save) => Roll::Save(save.into()),
+            Expr::Repeated(n, roll) => Roll::Repeated(n, Box::new((*roll).into())),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::damage::DamagePart::Dice as D;
-    use crate::damage::DamagePart::Modifier as M;
     use crate::*;
 
+    fn dice(num: u32, sides: Sides) -> Modifier {
+        Modifier::from_dice(num, sides)
+    }
+
+    fn constant(value: Score) -> Modifier {
+        Modifier::from_constant(value)
+    }
+
     #[test]
     fn damage_basic() {
         assert_eq!(
             "2d10".parse::<Roll>().unwrap(),
-            Roll::Damage(Damage(vec![D(2, 10)]))
+            Roll::Damage(Damage::from(vec![dice(2, 10)]))
         );
         assert_eq!(
             "5d6".parse::<Roll>().unwrap(),
-            Roll::Damage(Damage(vec![D(5, 6)]))
+            Roll::Damage(Damage::from(vec![dice(5, 6)]))
         );
         assert_eq!(
             "25d4".parse::<Roll>().unwrap(),
-            Roll::Damage(Damage(vec![D(25, 4)]))
+            Roll::Damage(Damage::from(vec![dice(25, 4)]))
         );
     }
 
@@ -44,23 +55,28 @@ mod tests {
     fn damage_sum() {
         assert_eq!(
             "3d4+5".parse::<Roll>().unwrap(),
-            Roll::Damage(Damage(vec![D(3, 4), M(5)]))
+            Roll::Damage(Damage::from(vec![dice(3, 4), constant(5)]))
         );
         assert_eq!(
             "2d6+4".parse::<Roll>().unwrap(),
-            Roll::Damage(Damage(vec![D(2, 6), M(4)]))
+            Roll::Damage(Damage::from(vec![dice(2, 6), constant(4)]))
         );
         assert_eq!(
             "3d4-5".parse::<Roll>().unwrap(),
-            Roll::Damage(Damage(vec![D(3, 4), M(-5)]))
+            Roll::Damage(Damage::from(vec![dice(3, 4), constant(-5)]))
         );
         assert_eq!(
             "7d6+2d8+9".parse::<Roll>().unwrap(),
-            Roll::Damage(Damage(vec![D(7, 6), D(2, 8), M(9)]))
+            Roll::Damage(Damage::from(vec![dice(7, 6), dice(2, 8), constant(9)]))
         );
         assert_eq!(
             "2d8-1d4-1+5".parse::<Roll>().unwrap(),
-            Roll::Damage(Damage(vec![D(2, 8), D(1, -4), M(-1), M(5)]))
+            Roll::Damage(Damage::from(vec![
+                dice(2, 8),
+                dice(1, -4),
+                constant(-1),
+                constant(5)
+            ]))
         );
     }
 
@@ -101,6 +117,16 @@ mod tests {
             "r+1d4+2".parse::<Roll>().unwrap(),
             Roll::Check(Check::new("r+1d4+2").unwrap())
         );
+
+        // `d1` would be a die on its own
+        assert_eq!(
+            "d1d4".parse::<Roll>().unwrap(),
+            Roll::Check(Check::new("d+1d4").unwrap())
+        );
+        assert_eq!(
+            "r+?1d6".parse::<Roll>().unwrap(),
+            Roll::Attack(Attack::new("r?1d6").unwrap())
+        );
     }
 
     #[test]
@@ -141,4 +167,183 @@ mod tests {
             Roll::Attack(Attack::new("a+1d4+3-1?1d4+4d6+2-1d4").unwrap())
         );
     }
+
+    #[test]
+    fn targets() {
+        assert_eq!(
+            "r+5 vs 15".parse::<Roll>().unwrap(),
+            Roll::Check(Check::new("r+5").unwrap().with_target(15))
+        );
+
+        assert_eq!(
+            "r+7?1d8+4 vs AC 16".parse::<Roll>().unwrap(),
+            Roll::Attack(Attack::new("r+7?1d8+4").unwrap().with_target(16))
+        );
+
+        assert!("2d6 vs 15".parse::<Roll>().is_err());
+    }
+
+    #[test]
+    fn saves() {
+        assert_eq!(
+            "save DC15 dex +3 : 8d6 half".parse::<Roll>().unwrap(),
+            Roll::Save(Save::new("save DC15 dex +3 : 8d6 half").unwrap())
+        );
+        assert_eq!(
+            "2x save DC15 dex +3 : 8d6 half".parse::<Roll>().unwrap(),
+            Roll::Repeated(
+                2,
+                Box::new(Roll::Save(
+                    Save::new("save DC15 dex +3 : 8d6 half").unwrap()
+                ))
+            )
+        );
+
+        assert!("save".parse::<Roll>().is_err());
+    }
+
+    #[test]
+    fn repeated() {
+        assert_eq!(
+            "3x r+5?1d8+3".parse::<Roll>().unwrap(),
+            Roll::Repeated(3, Box::new(Roll::Attack(Attack::new("r+5?1d8+3").unwrap())))
+        );
+        assert_eq!(
+            "2xr+5 vs 15".parse::<Roll>().unwrap(),
+            Roll::Repeated(
+                2,
+                Box::new(Roll::Check(Check::new("r+5").unwrap().with_target(15)))
+            )
+        );
+        assert_eq!(
+            "4x 2d6+1".parse::<Roll>().unwrap(),
+            Roll::Repeated(
+                4,
+                Box::new(Roll::Damage(Damage::from(vec![dice(2, 6), constant(1)])))
+            )
+        );
+
+        assert!("0x r+5".parse::<Roll>().is_err());
+        assert!("2x 3x r+5".parse::<Roll>().is_err());
+        assert!("2x".parse::<Roll>().is_err());
+        assert!("x r+5".parse::<Roll>().is_err());
+    }
+
+    #[test]
+    fn implicit_count() {
+        assert_eq!(
+            "d8".parse::<Roll>().unwrap(),
+            Roll::Damage(Damage::from(vec![dice(1, 8)]))
+        );
+        assert_eq!(
+            "r+5?d8+3".parse::<Roll>().unwrap(),
+            Roll::Attack(Attack::new("r+5?1d8+3").unwrap())
+        );
+
+        // A `d` that isn't followed by a number of sides is still
+        // disadvantage
+        assert_eq!(
+            "d+2".parse::<Roll>().unwrap(),
+            Roll::Check(Check::new("d+2").unwrap())
+        );
+        assert_eq!(
+            "dc19".parse::<Roll>().unwrap(),
+            Roll::Check(Check::new("d").unwrap().with_crit_range(19))
+        );
+    }
+
+    #[test]
+    fn whitespace_and_case() {
+        assert_eq!(
+            "R + 5 ? 2D6 + 3".parse::<Roll>().unwrap(),
+            Roll::Attack(Attack::new("r+5?2d6+3").unwrap())
+        );
+        assert_eq!(
+            "A+7C19 MIN10 VS DC20".parse::<Roll>().unwrap(),
+            Roll::Check(Check::new("a+7c19 min10 vs 20").unwrap())
+        );
+        assert_eq!(
+            "SAVE DC15 DEX +3 : 8D6 Fire HALF".parse::<Roll>().unwrap(),
+            Roll::Save(Save::new("save DC15 dex +3 : 8d6 fire half").unwrap())
+        );
+    }
+
+    #[test]
+    fn parentheses() {
+        assert_eq!(
+            "(2d6+3)".parse::<Roll>().unwrap(),
+            Roll::Damage(Damage::from(vec![dice(2, 6), constant(3)]))
+        );
+        assert_eq!(
+            "r+(1d4+2)?(1d8+3)".parse::<Roll>().unwrap(),
+            Roll::Attack(Attack::new("r+1d4+2?1d8+3").unwrap())
+        );
+    }
+
+    #[test]
+    fn variables() {
+        use std::collections::HashMap;
+
+        let roll = "3x r+$str+$prof?1d8+$str-$penalty".parse::<Roll>().unwrap();
+        assert_eq!(roll.variables(), vec!["str", "prof", "penalty"]);
+        assert_eq!(
+            "$str-$dex".parse::<Roll>().unwrap(),
+            Roll::Damage(Damage::from(vec![
+                Modifier::from_variable("str"),
+                -Modifier::from_variable("dex")
+            ]))
+        );
+
+        let mut context: HashMap<String, Score> = HashMap::new();
+        context.insert("str".into(), 4);
+        context.insert("prof".into(), 3);
+        assert_eq!(roll.bind(&context).unwrap_err().name(), "penalty");
+
+        context.insert("penalty".into(), 1);
+        assert_eq!(
+            roll.bind(&context).unwrap(),
+            Roll::new("3x r+4+3?1d8+4-1").unwrap()
+        );
+        assert_eq!(
+            Roll::new("(1d6+$str)*2 fire")
+                .unwrap()
+                .bind(&context)
+                .unwrap(),
+            Roll::new("(1d6+4)*2 fire").unwrap()
+        );
+    }
+
+    #[test]
+    fn errors() {
+        use crate::ParseErrorKind::*;
+
+        let err = |s: &str| {
+            let err = s.parse::<Roll>().unwrap_err();
+            (err.kind(), err.span())
+        };
+
+        assert_eq!(err(""), (Empty, 0..0));
+        assert_eq!(err("r+q"), (UnknownToken, 2..3));
+        assert_eq!(err("r+5?0d6"), (EmptyDiceCount, 4..7));
+        assert_eq!(err("2d0+3"), (ZeroSidedDie, 0..3));
+        assert_eq!(err("2d6+"), (DanglingOperator, 3..4));
+        assert_eq!(err("2d6 + 3 -"), (DanglingOperator, 8..9));
+        assert_eq!(err("r+$"), (UnknownToken, 2..3));
+        assert_eq!(err("r+ vs 10"), (UnknownToken, 3..5));
+        assert_eq!(err("(2d6+3)*"), (DanglingOperator, 7..8));
+        assert_eq!(err("8d6/0"), (InvalidNumber, 4..5));
+        assert_eq!(err("(1d6 fire)/2"), (Unexpected, 0..12));
+        assert_eq!(err("r+5?1d8?1d6"), (MultipleQuestionMarks, 7..8));
+        assert_eq!(err("a+5c25 vs 15"), (InvalidNumber, 4..6));
+        assert_eq!(err("4d6kh3kl1"), (InvalidDiceRule, 6..9));
+        assert_eq!(err("4d6kh5"), (InvalidDiceRule, 3..6));
+        assert_eq!(err("1d8+2d6 crit 1,1"), (InvalidNumber, 15..16));
+        assert_eq!(err("8d6min7"), (InvalidDiceRule, 3..7));
+        assert_eq!(err("2x r+5?1d6 holy"), (UnknownToken, 11..15));
+        assert_eq!(err("save DC15 dex +3 : 8d0 half"), (ZeroSidedDie, 19..22));
+
+        let holy = "1d6 holy".parse::<Roll>().unwrap_err();
+        assert_eq!(holy.found(), "holy");
+        assert!(holy.expected().contains(&"radiant"));
+    }
 }
@@ -0,0 +1,165 @@
+use rand::Rng;
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::RollExpression;
+use crate::{util, Ability, AdvState, Check, Damage, Score, UnboundVariable};
+
+pub use saveoutcome::SaveOutcome;
+
+mod saveoutcome;
+mod saveparse;
+
+/// What happens to the damage of a save-based effect when the target
+/// succeeds on the save.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SaveEffect {
+    /// The target takes half damage (rounded down) on a success
+    /// (`half`).
+    #[default]
+    Half,
+    /// The target takes no damage on a success (`negates`).
+    Negates,
+}
+
+/// A save-based effect, like a Fireball: the target rolls a saving
+/// throw against a DC, and damage is rolled once.
+///
+/// ```
+/// use critfail::{RollExpression, Save};
+///
+/// let fireball = Save::new("save DC15 dex +3 : 8d6 half").unwrap();
+///
+/// let outcome = fireball.roll();
+///
+/// print!("{}", outcome);   // Prints something like "Saved (17) : 14"
+/// print!("{:?}", outcome); // Prints something like "(14)+3 vs 15 : ([3+4+...])/2"
+/// ```
+///
+/// The target's ability and the effect on a success can be left out.
+/// Save effects deal half damage on a success unless they are marked
+/// with `negates`.
+///
+/// ```
+/// use critfail::{RollExpression, Save, SaveEffect};
+///
+/// let save = Save::new("save DC13 +1 : 2d8").unwrap();
+/// assert_eq!(save.ability(), None);
+/// assert_eq!(save.effect(), SaveEffect::Half);
+///
+/// let save = Save::new("save DC12 con a+2 : 1d12 negates").unwrap();
+/// assert_eq!(save.effect(), SaveEffect::Negates);
+/// ```
+#[derive(PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Save {
+    ability: Option<Ability>,
+    check: Check,
+    damage: Damage,
+    effect: SaveEffect,
+}
+
+impl Save {
+    /// Get the DC of the saving throw.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Save};
+    ///
+    /// assert_eq!(Save::new("save DC15 dex +3 : 8d6 half").unwrap().dc(), 15);
+    /// ```
+    pub fn dc(&self) -> Score {
+        self.check.target().unwrap_or_default()
+    }
+
+    /// Get the ability the saving throw is made with, if it was given.
+    pub fn ability(&self) -> Option<Ability> {
+        self.ability
+    }
+
+    /// Get what happens to the damage when the target succeeds on the
+    /// saving throw.
+    pub fn effect(&self) -> SaveEffect {
+        self.effect
+    }
+
+    /// Get the names of the variables in this save (without the `$`),
+    /// in the order they first appear.
+    pub fn variables(&self) -> Vec<&str> {
+        let mut names = self.check.variables();
+        util::merge_names(&mut names, self.damage.variables());
+        names
+    }
+
+    /// Replace each variable in this save with its value in `context`.
+    pub fn bind(&self, context: &HashMap<String, Score>) -> Result<Self, UnboundVariable> {
+        Ok(Self {
+            check: self.check.bind(context)?,
+            damage: self.damage.bind(context)?,
+            ..self.clone()
+        })
+    }
+
+    /// Roll this save using `adv` to override the advantage state of
+    /// the saving throw.
+    ///
+    /// ```
+    /// use critfail::{RollExpression, Save, AdvState};
+    /// let save = Save::new("save DC15 dex +3 : 8d6 half").unwrap();
+    ///
+    /// save.roll_with_advantage(AdvState::Advantage); // Eg. a target with Magic Resistance
+    /// ```
+    pub fn roll_with_advantage(&self, adv: AdvState) -> SaveOutcome {
+        self.roll_with_advantage_and_rng(adv, &mut rand::thread_rng())
+    }
+
+    /// Roll this save using `adv` to override the advantage state of
+    /// the saving throw, and `rng` as the source of randomness.
+    pub fn roll_with_advantage_and_rng<R: Rng + ?Sized>(
+        &self,
+        adv: AdvState,
+        rng: &mut R,
+    ) -> SaveOutcome {
+        let check = self.check.roll_with_advantage_and_rng(adv, rng);
+        let damage = self.damage.roll_with_rng(rng);
+
+        SaveOutcome::new(check, damage, self.effect)
+    }
+}
+
+impl RollExpression for Save {
+    type Outcome = SaveOutcome;
+
+    fn roll_with_rng<R: Rng + ?Sized>(&self, rng: &mut R) -> Self::Outcome {
+        self.roll_with_advantage_and_rng(self.check.adv, rng)
+    }
+}
+
+/// Saves are written in a canonical form of the notation they are
+/// parsed from, so parsing the written save gives the same `Save` back.
+///
+/// ```
+/// use critfail::{RollExpression, Save};
+///
+/// let save = Save::new("save dc 13 wis: 3d6 psychic").unwrap();
+/// assert_eq!(save.to_string(), "save DC13 wis : 3d6 psychic half");
+/// assert_eq!(Save::new(&save.to_string()).unwrap(), save);
+/// ```
+impl fmt::Display for Save {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "save DC{}", self.dc())?;
+        if let Some(ability) = self.ability {
+            write!(f, " {}", ability)?;
+        }
+        if !self.check.is_plain() {
+            write!(f, " ")?;
+            self.check.write_without_target(f)?;
+        }
+
+        let effect = match self.effect {
+            SaveEffect::Half => "half",
+            SaveEffect::Negates => "negates",
+        };
+        write!(f, " : {} {}", self.damage, effect)
+    }
+}
@@ -0,0 +1,154 @@
+use std::fmt;
+
+use crate::{CheckOutcome, DamageOutcome, SaveEffect, Score};
+
+/// The outcome of a save-based effect.
+///
+/// This struct is normally constructed as the result of calling
+/// `roll()` on a `Save` roll expression.
+///
+/// ```
+/// use critfail::{RollExpression, Save, SaveOutcome};
+///
+/// let outcome: SaveOutcome = Save::new("save DC15 dex +3 : 8d6 half").unwrap().roll();
+///
+/// if outcome.saved() {
+///     assert_eq!(outcome.score(), outcome.damage().score() / 2);
+/// } else {
+///     assert_eq!(outcome.score(), outcome.damage().score());
+/// }
+/// ```
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SaveOutcome {
+    check: CheckOutcome,
+    damage: DamageOutcome,
+    effect: SaveEffect,
+}
+
+impl SaveOutcome {
+    pub(crate) fn new(check: CheckOutcome, damage: DamageOutcome, effect: SaveEffect) -> Self {
+        Self {
+            check,
+            damage,
+            effect,
+        }
+    }
+
+    /// Get the saving throw portion of this `SaveOutcome`.
+    pub fn check(&self) -> &CheckOutcome {
+        &self.check
+    }
+
+    /// Get the damage that was rolled, before it is reduced by a
+    /// successful save.
+    pub fn damage(&self) -> &DamageOutcome {
+        &self.damage
+    }
+
+    /// Get whether the target succeeded on the saving throw.
+    ///
+    /// Unlike attacks, a natural 20 or a natural 1 on a saving throw
+    /// isn't treated specially; the save succeeds if it meets or beats
+    /// the DC.
+    pub fn saved(&self) -> bool {
+        self.check.score() >= self.check.target().unwrap_or_default()
+    }
+
+    /// Get the damage the target takes.
+    pub fn score(&self) -> Score {
+        let damage = self.damage.score();
+
+        match (self.saved(), self.effect) {
+            (false, _) => damage,
+            (true, SaveEffect::Half) => damage.div_euclid(2),
+            (true, SaveEffect::Negates) => 0,
+        }
+    }
+}
+
+impl fmt::Display for SaveOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.saved() {
+            write!(f, "Saved ({}) : {}", self.check.score(), self.score())
+        } else {
+            write!(f, "Failed ({}) : {}", self.check.score(), self.score())
+        }
+    }
+}
+
+impl fmt::Debug for SaveOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} : ", self.check)?;
+
+        match (self.saved(), self.effect) {
+            (false, _) => write!(f, "{:?}", self.damage),
+            (true, SaveEffect::Half) => write!(f, "({:?})/2", self.damage),
+            (true, SaveEffect::Negates) => write!(f, "({:?})*0", self.damage),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AdvState::*;
+    use crate::OutcomePart::Dice as D;
+    use crate::OutcomePart::Modifier as M;
+
+    fn save(d20: Score, effect: SaveEffect) -> SaveOutcome {
+        SaveOutcome::new(
+            CheckOutcome::new(Neutral, d20, 10, vec![M(3)]).with_target(Some(15)),
+            DamageOutcome::new(vec![D(6, vec![3, 4, 6, 2])]),
+            effect,
+        )
+    }
+
+    #[test]
+    fn failed() {
+        let r = save(11, SaveEffect::Half);
+        assert!(!r.saved());
+        assert_eq!(r.score(), 15);
+        assert_eq!(format!("{}", r), "Failed (14) : 15");
+        assert_eq!(format!("{:?}", r), "(11)+3 vs 15 : [3+4+6+2]");
+    }
+
+    #[test]
+    fn half() {
+        let r = save(12, SaveEffect::Half);
+        assert!(r.saved());
+        assert_eq!(r.score(), 7);
+        assert_eq!(format!("{}", r), "Saved (15) : 7");
+        assert_eq!(format!("{:?}", r), "(12)+3 vs 15 : ([3+4+6+2])/2");
+    }
+
+    #[test]
+    fn negates() {
+        let r = save(12, SaveEffect::Negates);
+        assert_eq!(r.score(), 0);
+        assert_eq!(format!("{}", r), "Saved (15) : 0");
+        assert_eq!(format!("{:?}", r), "(12)+3 vs 15 : ([3+4+6+2])*0");
+
+        let r = save(11, SaveEffect::Negates);
+        assert_eq!(r.score(), 15);
+    }
+
+    #[test]
+    fn natural_rolls() {
+        // A natural 20 doesn't automatically succeed on a save
+        let r = SaveOutcome::new(
+            CheckOutcome::new(Neutral, 20, 10, vec![M(-6)]).with_target(Some(15)),
+            DamageOutcome::new(vec![D(6, vec![3, 4])]),
+            SaveEffect::Half,
+        );
+        assert!(!r.saved());
+
+        // And a natural 1 doesn't automatically fail
+        let r = SaveOutcome::new(
+            CheckOutcome::new(Neutral, 1, 10, vec![M(14)]).with_target(Some(15)),
+            DamageOutcome::new(vec![D(6, vec![3, 4])]),
+            SaveEffect::Half,
+        );
+        assert!(r.saved());
+    }
+}
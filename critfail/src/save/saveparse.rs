@@ -0,0 +1,92 @@
+use std::str::FromStr;
+
+use crate::parse::{self, SaveExpr};
+use crate::{Check, ParseError, Save};
+
+impl FromStr for Save {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(parse::save(s)?.into())
+    }
+}
+
+impl From<SaveExpr> for Save {
+    fn from(expr: SaveExpr) -> Self {
+        // The saving throw is a check against the DC
+        Save {
+            ability: expr.ability,
+            check: Check::from(expr.check).with_target(expr.dc),
+            damage: expr.damage.into(),
+            effect: expr.effect,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Ability, Damage, RollExpression, SaveEffect};
+
+    #[test]
+    fn fireball() {
+        assert_eq!(
+            "save DC15 dex +3 : 8d6 half".parse::<Save>().unwrap(),
+            Save {
+                ability: Some(Ability::Dexterity),
+                check: Check::new("r+3").unwrap().with_target(15),
+                damage: Damage::new("8d6").unwrap(),
+                effect: SaveEffect::Half,
+            }
+        );
+    }
+
+    #[test]
+    fn negates() {
+        assert_eq!(
+            "save dc 12 con a-1:1d12 poison negates"
+                .parse::<Save>()
+                .unwrap(),
+            Save {
+                ability: Some(Ability::Constitution),
+                check: Check::new("a-1").unwrap().with_target(12),
+                damage: Damage::new("1d12 poison").unwrap(),
+                effect: SaveEffect::Negates,
+            }
+        );
+    }
+
+    #[test]
+    fn optional_parts() {
+        assert_eq!(
+            "save DC13 : 2d8+1".parse::<Save>().unwrap(),
+            Save {
+                ability: None,
+                check: Check::new("r").unwrap().with_target(13),
+                damage: Damage::new("2d8+1").unwrap(),
+                effect: SaveEffect::Half,
+            }
+        );
+
+        assert_eq!(
+            "save DC13 wis : 3d6 psychic".parse::<Save>().unwrap(),
+            Save {
+                ability: Some(Ability::Wisdom),
+                check: Check::new("r").unwrap().with_target(13),
+                damage: Damage::new("3d6 psychic").unwrap(),
+                effect: SaveEffect::Half,
+            }
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        assert!("save : 8d6 half".parse::<Save>().is_err());
+        assert!("save DC15 dex +3".parse::<Save>().is_err());
+        assert!("save DC15 dex +3 :".parse::<Save>().is_err());
+        assert!("save DC15 luck +3 : 8d6".parse::<Save>().is_err());
+        assert!("save DC15 +3 vs 12 : 8d6".parse::<Save>().is_err());
+        assert!("save DC15 +3 : 8d6 twice".parse::<Save>().is_err());
+        assert!("DC15 +3 : 8d6".parse::<Save>().is_err());
+    }
+}
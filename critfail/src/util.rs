@@ -12,3 +12,13 @@ where
     }
     Ok(())
 }
+
+/// Add the names in `more` to `names`, skipping any that are already in
+/// it.
+pub(crate) fn merge_names<'a>(names: &mut Vec<&'a str>, more: Vec<&'a str>) {
+    for name in more {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+}